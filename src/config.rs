@@ -0,0 +1,225 @@
+use color_eyre::eyre::{self, WrapErr};
+use glob::Pattern;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Substrate primitives that are the same for every runtime.
+const DEFAULT_TYPES: &[(&str, &str)] = &[
+    ("H160", "sp_core::H160"),
+    ("H256", "sp_core::H256"),
+    ("AccountId32", "sp_core::crypto::AccountId32"),
+    ("Perbill", "sp_arithmetic::Perbill"),
+    ("Permill", "sp_arithmetic::Permill"),
+    ("Percent", "sp_arithmetic::Percent"),
+    ("FixedU128", "sp_arithmetic::FixedU128"),
+    ("MultiAddress", "sp_runtime::MultiAddress"),
+    ("DispatchResult", "sp_runtime::DispatchResult"),
+    ("Weight", "u64"),
+    ("Moment", "u64"),
+];
+
+/// Options controlling what gets generated.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Glob patterns of the pallets to generate, all pallets if empty.
+    pub include: Vec<String>,
+    /// Glob patterns of the pallets to skip, takes priority over `include`.
+    pub exclude: Vec<String>,
+    /// Glob patterns of the pallets skipped unless they match `include`,
+    /// defaults to the pallets provided by `substrate_subxt` itself.
+    pub default_exclude: Vec<String>,
+    /// Selectors of the form `Pallet::item` or `Pallet::item::kind` where kind
+    /// is one of `calls`, `events`, `storage`, `constants` or `errors`, both
+    /// names may be globs.
+    /// Pallets without a matching selector keep all of their items.
+    pub items: Vec<String>,
+    /// Associated types to use for qualified paths given as `Trait::Item`
+    /// and for plain type names, e.g. `StaticLookup::Source` to `Address`.
+    pub associated_types: HashMap<String, String>,
+    /// Rust paths to use for metadata type names instead of associated
+    /// types, e.g. `Balance` to `u128`, defaults to common primitives.
+    pub types: HashMap<String, String>,
+    /// Associated types declared by `substrate_subxt::system::System`,
+    /// pallets use these instead of declaring their own.
+    pub system_types: Vec<String>,
+    /// Suffix for names that are keywords, such as `type_` for `type`,
+    /// raw identifiers like `r#type` are used if not set.
+    pub ident_suffix: Option<String>,
+    /// Skip items that cannot be generated with a warning instead of failing.
+    pub keep_going: bool,
+    /// Also embed the values of primitive constants from the metadata
+    /// the code is generated from.
+    pub embed_constants: bool,
+}
+
+/// The kinds of items generated for a pallet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ItemKind {
+    Call,
+    Event,
+    Storage,
+    Constant,
+    Error,
+}
+
+impl std::str::FromStr for ItemKind {
+    type Err = color_eyre::Report;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "calls" => Ok(Self::Call),
+            "events" => Ok(Self::Event),
+            "storage" => Ok(Self::Storage),
+            "constants" => Ok(Self::Constant),
+            "errors" => Ok(Self::Error),
+            _ => Err(eyre::eyre!(
+                "Unknown item kind {}, expected calls, events, storage, constants or errors",
+                s
+            )),
+        }
+    }
+}
+
+struct ItemSelector {
+    module: Pattern,
+    item: Pattern,
+    kind: Option<ItemKind>,
+}
+
+impl std::str::FromStr for ItemSelector {
+    type Err = color_eyre::Report;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split("::").collect::<Vec<_>>();
+        let (module, item, kind) = match parts.as_slice() {
+            [module, item] => (module, item, None),
+            [module, item, kind] => (module, item, Some(kind.parse()?)),
+            _ => return Err(eyre::eyre!("Invalid item selector {}", s)),
+        };
+        Ok(Self {
+            module: Pattern::new(module)?,
+            item: Pattern::new(item)?,
+            kind,
+        })
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            default_exclude: vec!["System".to_string()],
+            items: Vec::new(),
+            associated_types: [
+                ("StaticLookup::Source", "Address"),
+                ("LookupSource", "Address"),
+            ]
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect(),
+            types: DEFAULT_TYPES
+                .iter()
+                .map(|(from, to)| (from.to_string(), to.to_string()))
+                .collect(),
+            system_types: [
+                "Index",
+                "BlockNumber",
+                "Hash",
+                "Hashing",
+                "AccountId",
+                "Address",
+                "Header",
+                "Extrinsic",
+                "AccountData",
+            ]
+            .iter()
+            .map(|ty| ty.to_string())
+            .collect(),
+            ident_suffix: None,
+            keep_going: false,
+            embed_constants: false,
+        }
+    }
+}
+
+fn matches_any(patterns: &[String], name: &str) -> color_eyre::Result<bool> {
+    for pattern in patterns {
+        if Pattern::new(pattern)?.matches(name) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+impl Config {
+    /// Add the type substitutions from a TOML or JSON file mapping
+    /// type names to Rust paths, replacing existing entries.
+    pub fn read_types(&mut self, path: &Path) -> color_eyre::Result<()> {
+        let context = || format!("error reading types from {}", path.display());
+        let contents = std::fs::read_to_string(path).with_context(context)?;
+
+        let types: HashMap<String, String> = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents).with_context(context)?,
+            Some("json") => serde_json::from_str(&contents).with_context(context)?,
+            _ => {
+                return Err(eyre::eyre!(
+                    "{} should be a .toml or .json file",
+                    path.display()
+                ))
+            }
+        };
+
+        self.types.extend(types);
+        Ok(())
+    }
+
+    /// Turn the error of an item into a warning if we keep going, in
+    /// which case the item is skipped.
+    pub(crate) fn recover<T>(
+        &self,
+        result: color_eyre::Result<T>,
+    ) -> color_eyre::Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if self.keep_going => {
+                eprintln!("warning: skipping item: {:#}", err);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Whether code should be generated for the pallet with the given name.
+    pub fn includes_module(&self, name: &str) -> color_eyre::Result<bool> {
+        if matches_any(&self.exclude, name)? {
+            return Ok(false);
+        }
+        if self.include.is_empty() {
+            return Ok(!matches_any(&self.default_exclude, name)?);
+        }
+        // an explicit include overrides the default exclusions
+        matches_any(&self.include, name)
+    }
+
+    /// Whether code should be generated for the item of a pallet.
+    pub fn includes_item(
+        &self,
+        module: &str,
+        kind: ItemKind,
+        name: &str,
+    ) -> color_eyre::Result<bool> {
+        let selectors = self
+            .items
+            .iter()
+            .map(|selector| selector.parse::<ItemSelector>())
+            .collect::<color_eyre::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|selector| selector.module.matches(module))
+            .collect::<Vec<_>>();
+
+        Ok(selectors.is_empty()
+            || selectors.iter().any(|selector| {
+                selector.item.matches(name)
+                    && (selector.kind.is_none() || selector.kind == Some(kind))
+            }))
+    }
+}
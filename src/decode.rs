@@ -0,0 +1,289 @@
+use color_eyre::eyre;
+use frame_metadata::decode_different::{DecodeDifferent, DecodeDifferentArray};
+
+pub trait DecodeArrayOrElse<T> {
+    fn decode_array_or_else(self) -> color_eyre::Result<Vec<T>>;
+}
+
+impl<T> DecodeArrayOrElse<T> for DecodeDifferentArray<T> {
+    fn decode_array_or_else(self) -> color_eyre::Result<Vec<T>> {
+        match self {
+            DecodeDifferent::Decoded(value) => Ok(value),
+            DecodeDifferent::Encode(_) => Err(eyre::eyre!("Metadata should be Decoded")),
+        }
+    }
+}
+
+pub trait DecodeOrElse<T> {
+    fn decode_or_else(self) -> color_eyre::Result<T>;
+}
+
+impl<B, O> DecodeOrElse<O> for DecodeDifferent<B, O> {
+    fn decode_or_else(self) -> color_eyre::Result<O> {
+        match self {
+            DecodeDifferent::Decoded(value) => Ok(value),
+            DecodeDifferent::Encode(_) => Err(eyre::eyre!("Metadata should be Decoded")),
+        }
+    }
+}
+
+/// Generates `add_module` for the metadata versions built on `DecodeDifferent`,
+/// these only differ in their storage entry types so anything beyond plain,
+/// map and double map entries is given as additional match arms.
+macro_rules! decode_module {
+    ($version:ident $(, $entry_ty:pat => |$config:ident| $decoded_ty:expr)* $(,)?) => {
+        use crate::config::ItemKind;
+        use crate::decode::DecodeOrElse;
+        use color_eyre::eyre::WrapErr;
+        use crate::module::{
+            self, ConstantMetadata, ErrorMetadata, EventMetadata, FunctionArgumentMetadata, FunctionMetadata, ModuleMetadata,
+            StorageEntryMetadata, StorageEntryType,
+        };
+        use crate::{Config, RuntimeType};
+        use frame_metadata::$version;
+
+        pub fn add_module(
+            module: $version::ModuleMetadata,
+            index: u8,
+            config: &Config,
+            modules: &mut module::Modules,
+        ) -> color_eyre::Result<()> {
+            let name = module.name.decode_or_else()?;
+
+            if !config.includes_module(&name)? {
+                return Ok(());
+            }
+
+            let calls = if let Some(calls) = module.calls {
+                calls
+                    .decode_or_else()?
+                    .into_iter()
+                    .map(|call| decode_call(call, &name, config))
+                    .map(|call| {
+                        call.with_context(|| {
+                            format!("error decoding a call of pallet `{}`", name)
+                        })
+                    })
+                    .filter_map(|call| config.recover(call).map(Option::flatten).transpose())
+                    .collect::<color_eyre::Result<_>>()?
+            } else {
+                Vec::new()
+            };
+
+            let events = if let Some(events) = module.event {
+                events
+                    .decode_or_else()?
+                    .into_iter()
+                    .map(|event| decode_event(event, &name, config))
+                    .map(|event| {
+                        event.with_context(|| {
+                            format!("error decoding an event of pallet `{}`", name)
+                        })
+                    })
+                    .filter_map(|event| config.recover(event).map(Option::flatten).transpose())
+                    .collect::<color_eyre::Result<_>>()?
+            } else {
+                Vec::new()
+            };
+
+            let storage = if let Some(storage) = module.storage {
+                storage
+                    .decode_or_else()?
+                    .entries
+                    .decode_or_else()?
+                    .into_iter()
+                    .map(|entry| decode_storage_entry(entry, &name, config))
+                    .map(|entry| {
+                        entry.with_context(|| {
+                            format!("error decoding a storage entry of pallet `{}`", name)
+                        })
+                    })
+                    .filter_map(|entry| config.recover(entry).map(Option::flatten).transpose())
+                    .collect::<color_eyre::Result<_>>()?
+            } else {
+                Vec::new()
+            };
+
+            let constants = module
+                .constants
+                .decode_or_else()?
+                .into_iter()
+                .map(|constant| decode_constant(constant, &name, config))
+                .map(|constant| {
+                    constant.with_context(|| {
+                        format!("error decoding a constant of pallet `{}`", name)
+                    })
+                })
+                .filter_map(|constant| config.recover(constant).map(Option::flatten).transpose())
+                .collect::<color_eyre::Result<_>>()?;
+
+            let errors = module
+                .errors
+                .decode_or_else()?
+                .into_iter()
+                .zip(0..)
+                .map(|(error, index)| decode_error(error, index, &name, config))
+                .map(|error| {
+                    error.with_context(|| {
+                        format!("error decoding an error of pallet `{}`", name)
+                    })
+                })
+                .filter_map(|error| config.recover(error).map(Option::flatten).transpose())
+                .collect::<color_eyre::Result<_>>()?;
+
+            module::add_module(
+                ModuleMetadata {
+                    name,
+                    index,
+                    calls,
+                    events,
+                    storage,
+                    constants,
+                    errors,
+                },
+                config,
+                modules,
+            )
+        }
+
+        fn decode_call(
+            fn_meta: $version::FunctionMetadata,
+            module_name: &str,
+            config: &Config,
+        ) -> color_eyre::Result<Option<FunctionMetadata>> {
+            let name = fn_meta.name.decode_or_else()?;
+            if !config.includes_item(module_name, ItemKind::Call, &name)? {
+                return Ok(None);
+            }
+
+            let arguments = fn_meta
+                .arguments
+                .decode_or_else()?
+                .into_iter()
+                .map(|arg| {
+                    let arg_name = arg.name.decode_or_else()?;
+                    let ty = RuntimeType::parse(&arg.ty.decode_or_else()?, config).with_context(|| {
+                        format!("error parsing argument `{}` of call `{}`", arg_name, name)
+                    })?;
+                    Ok(FunctionArgumentMetadata { name: arg_name, ty })
+                })
+                .collect::<color_eyre::Result<_>>()?;
+            Ok(Some(FunctionMetadata {
+                name,
+                arguments,
+                docs: fn_meta.documentation.decode_or_else()?,
+            }))
+        }
+
+        fn decode_event(
+            event_meta: $version::EventMetadata,
+            module_name: &str,
+            config: &Config,
+        ) -> color_eyre::Result<Option<EventMetadata>> {
+            let name = event_meta.name.decode_or_else()?;
+            if !config.includes_item(module_name, ItemKind::Event, &name)? {
+                return Ok(None);
+            }
+
+            let arguments = event_meta
+                .arguments
+                .decode_or_else()?
+                .iter()
+                .enumerate()
+                .map(|(i, arg)| {
+                    RuntimeType::parse(arg, config).with_context(|| {
+                        format!("error parsing argument {} of event `{}`", i, name)
+                    })
+                })
+                .collect::<color_eyre::Result<_>>()?;
+            Ok(Some(EventMetadata {
+                name,
+                arguments,
+                docs: event_meta.documentation.decode_or_else()?,
+            }))
+        }
+
+        fn decode_constant(
+            constant: $version::ModuleConstantMetadata,
+            module_name: &str,
+            config: &Config,
+        ) -> color_eyre::Result<Option<ConstantMetadata>> {
+            let name = constant.name.decode_or_else()?;
+            if !config.includes_item(module_name, ItemKind::Constant, &name)? {
+                return Ok(None);
+            }
+
+            let ty = RuntimeType::parse(&constant.ty.decode_or_else()?, config)
+                .with_context(|| format!("error parsing the type of constant `{}`", name))?;
+            Ok(Some(ConstantMetadata {
+                name,
+                ty,
+                value: constant.value.decode_or_else()?,
+                docs: constant.documentation.decode_or_else()?,
+            }))
+        }
+
+        fn decode_error(
+            error: $version::ErrorMetadata,
+            index: u8,
+            module_name: &str,
+            config: &Config,
+        ) -> color_eyre::Result<Option<ErrorMetadata>> {
+            let name = error.name.decode_or_else()?;
+            if !config.includes_item(module_name, ItemKind::Error, &name)? {
+                return Ok(None);
+            }
+
+            Ok(Some(ErrorMetadata {
+                name,
+                index,
+                docs: error.documentation.decode_or_else()?,
+            }))
+        }
+
+        fn decode_storage_entry(
+            entry: $version::StorageEntryMetadata,
+            module_name: &str,
+            config: &Config,
+        ) -> color_eyre::Result<Option<StorageEntryMetadata>> {
+            let name = entry.name.decode_or_else()?;
+            if !config.includes_item(module_name, ItemKind::Storage, &name)? {
+                return Ok(None);
+            }
+
+            let ty = decode_storage_entry_type(entry.ty, config)
+                .with_context(|| format!("error parsing the type of storage `{}`", name))?;
+            Ok(Some(StorageEntryMetadata {
+                name,
+                ty,
+                docs: entry.documentation.decode_or_else()?,
+            }))
+        }
+
+        fn decode_storage_entry_type(
+            ty: $version::StorageEntryType,
+            config: &Config,
+        ) -> color_eyre::Result<StorageEntryType> {
+            Ok(match ty {
+                $version::StorageEntryType::Plain(plain) => {
+                    StorageEntryType::Plain(RuntimeType::parse(&plain.decode_or_else()?, config)?)
+                }
+                $version::StorageEntryType::Map { key, value, .. } => StorageEntryType::Map {
+                    key: RuntimeType::parse(&key.decode_or_else()?, config)?,
+                    value: RuntimeType::parse(&value.decode_or_else()?, config)?,
+                },
+                $version::StorageEntryType::DoubleMap {
+                    key1, key2, value, ..
+                } => StorageEntryType::DoubleMap {
+                    key1: RuntimeType::parse(&key1.decode_or_else()?, config)?,
+                    key2: RuntimeType::parse(&key2.decode_or_else()?, config)?,
+                    value: RuntimeType::parse(&value.decode_or_else()?, config)?,
+                },
+                $($entry_ty => {
+                    let $config = config;
+                    $decoded_ty
+                })*
+            })
+        }
+    };
+}
@@ -1,33 +1,117 @@
 use color_eyre::eyre;
 use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote, TokenStreamExt};
 use std::str::FromStr;
 
+mod config;
+#[macro_use]
+mod decode;
+mod module;
+mod parse;
+mod rpc;
+mod source;
+mod v10;
+mod v11;
 mod v12;
+mod v13;
+mod v14;
+mod v9;
 
-use v12::DecodeArrayOrElse;
+pub use config::Config;
+use decode::DecodeArrayOrElse;
+pub use rpc::{fetch_block_hash, fetch_metadata, rpc_request};
+pub use source::{decode_json_response, parse_metadata};
 
-pub fn decode_metadata(metadata: RuntimeMetadataPrefixed) -> color_eyre::Result<TokenStream> {
-    let mut stream = quote! {
-        #![allow(dead_code)]
-        #![allow(unused_imports)]
-    };
+/// Generate the contents of each module, keyed by the module name.
+pub fn decode_modules(
+    metadata: RuntimeMetadataPrefixed,
+    config: &Config,
+) -> color_eyre::Result<Vec<(String, TokenStream)>> {
+    let mut modules = module::Modules::default();
+    let mut types_module = None;
 
     match metadata.1 {
+        RuntimeMetadata::V9(v9) => {
+            // modules are indexed by their position before V12
+            for (index, module) in (0..).zip(v9.modules.decode_array_or_else()?) {
+                v9::add_module(module, index, config, &mut modules)?;
+            }
+        }
+        RuntimeMetadata::V10(v10) => {
+            for (index, module) in (0..).zip(v10.modules.decode_array_or_else()?) {
+                v10::add_module(module, index, config, &mut modules)?;
+            }
+        }
+        RuntimeMetadata::V11(v11) => {
+            for (index, module) in (0..).zip(v11.modules.decode_array_or_else()?) {
+                v11::add_module(module, index, config, &mut modules)?;
+            }
+        }
         RuntimeMetadata::V12(v12) => {
-            let modules = v12.modules.decode_array_or_else()?;
+            for module in v12.modules.decode_array_or_else()? {
+                let index = module.index;
+                v12::add_module(module, index, config, &mut modules)?;
+            }
+        }
+        RuntimeMetadata::V13(v13) => {
+            for module in v13.modules.decode_array_or_else()? {
+                let index = module.index;
+                v13::add_module(module, index, config, &mut modules)?;
+            }
+        }
+        RuntimeMetadata::V14(v14) => {
+            let types = v14::TypeGenerator::new(&v14.types, config);
 
-            for module in modules {
-                v12::add_module(module, &mut stream)?;
+            for pallet in v14.pallets {
+                v14::add_module(pallet, &types, config, &mut modules)?;
             }
+
+            types_module = Some((v14::TYPES_MODULE.to_string(), types.generate()));
         }
         _ => return Err(eyre::eyre!("Unsupported metadata version")),
     };
 
+    let mut modules = modules.generate(config);
+    modules.extend(types_module);
+    Ok(modules)
+}
+
+pub fn decode_metadata(
+    metadata: RuntimeMetadataPrefixed,
+    config: &Config,
+) -> color_eyre::Result<TokenStream> {
+    let mut stream = quote! {
+        #![allow(dead_code)]
+        #![allow(unused_imports)]
+    };
+
+    for (name, module) in decode_modules(metadata, config)? {
+        let ident = format_ident!("{}", name);
+        stream.append_all(quote! {
+            pub mod #ident {
+                #module
+            }
+        });
+    }
+
     Ok(stream)
 }
 
+/// Same as [`decode_metadata`] but pretty-prints the generated code.
+pub fn decode_metadata_formatted(
+    metadata: RuntimeMetadataPrefixed,
+    config: &Config,
+) -> color_eyre::Result<String> {
+    format_stream(decode_metadata(metadata, config)?)
+}
+
+/// Pretty-print generated code, the stream must form a valid file.
+pub fn format_stream(stream: TokenStream) -> color_eyre::Result<String> {
+    let file = syn::parse2::<syn::File>(stream)?;
+    Ok(prettyplease::unparse(&file))
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum RuntimeType {
     Primitive(String),
@@ -35,16 +119,57 @@ pub enum RuntimeType {
     Option(Box<RuntimeType>),
     Vec(Box<RuntimeType>),
     Tuple(Box<Vec<RuntimeType>>),
+    Array(Box<RuntimeType>, usize),
+    /// Value encoded with the compact SCALE encoding.
+    Compact(Box<RuntimeType>),
+    /// Generic type applied to its arguments, the base is either
+    /// an associated type or a path.
+    Generic(Box<RuntimeType>, Vec<RuntimeType>),
+    /// Associated type of `substrate_subxt::system::System`.
+    System(String),
+    /// Fully resolved type that is used as is.
+    Path(String),
 }
 
 impl RuntimeType {
-    fn flatten_complex(&self) -> Vec<String> {
+    /// Parse a type from the string based metadata.
+    pub fn parse(s: &str, config: &Config) -> color_eyre::Result<Self> {
+        parse::parse_type(s.trim(), config)
+    }
+
+    /// The associated types used by this type with the number of generic
+    /// parameters they take and whether they are encoded compactly.
+    fn flatten_complex(&self) -> Vec<(String, usize, bool)> {
         match self {
             Self::Primitive(_) => vec![],
-            Self::Complex(ty) => vec![ty.to_string()],
+            Self::Complex(ty) => vec![(ty.to_string(), 0, false)],
             Self::Option(rt) => (**rt).flatten_complex(),
             Self::Vec(rt) => (**rt).flatten_complex(),
             Self::Tuple(rt) => (**rt).iter().flat_map(|rt| rt.flatten_complex()).collect(),
+            Self::Array(rt, _) => (**rt).flatten_complex(),
+            Self::Compact(rt) => {
+                let mut tys = (**rt).flatten_complex();
+                // the associated type comes first if it is the compact type itself
+                let compact = match &**rt {
+                    Self::Complex(_) => true,
+                    Self::Generic(base, _) => matches!(**base, Self::Complex(_)),
+                    _ => false,
+                };
+                if let (true, Some(ty)) = (compact, tys.first_mut()) {
+                    ty.2 = true;
+                }
+                tys
+            }
+            Self::Generic(base, args) => {
+                let base = match &**base {
+                    Self::Complex(ty) => vec![(ty.to_string(), args.len(), false)],
+                    base => base.flatten_complex(),
+                };
+                base.into_iter()
+                    .chain(args.iter().flat_map(|rt| rt.flatten_complex()))
+                    .collect()
+            }
+            Self::System(_) | Self::Path(_) => vec![],
         }
     }
 }
@@ -56,6 +181,8 @@ impl std::fmt::Display for RuntimeType {
             Self::Complex(ty) => write!(f, "T::{}", ty),
             Self::Option(rt) => write!(f, "Option<{}>", rt),
             Self::Vec(rt) => write!(f, "Vec<{}>", rt),
+            // a single element needs the trailing comma to remain a tuple
+            Self::Tuple(rt) if rt.len() == 1 => write!(f, "({},)", rt[0]),
             Self::Tuple(rt) => write!(
                 f,
                 "({})",
@@ -65,64 +192,26 @@ impl std::fmt::Display for RuntimeType {
                     .collect::<Vec<String>>()
                     .join(",")
             ),
+            Self::Array(rt, len) => write!(f, "[{}; {}]", rt, len),
+            Self::Compact(rt) => write!(f, "parity_scale_codec::Compact<{}>", rt),
+            Self::Generic(base, args) => write!(
+                f,
+                "{}<{}>",
+                base,
+                args.iter()
+                    .map(|rt| format!("{}", rt))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Self::System(ty) => write!(f, "<T as substrate_subxt::system::System>::{}", ty),
+            Self::Path(ty) => write!(f, "{}", ty),
         }
     }
 }
 
-macro_rules! match_literal {
-    ($string:expr, $start:expr, $stop:expr) => {
-        $string.starts_with($start) && $string.contains($stop)
-    };
-}
-
-macro_rules! inner_literal {
-    ($line:expr, $start:expr, $stop:expr) => {{
-        let start_bytes = $line.find($start).map(|i| i + $start.len()).unwrap_or(0);
-        let stop_bytes = $line.rfind($stop).unwrap_or($line.len());
-        &$line[start_bytes..stop_bytes]
-    }};
-}
-
-macro_rules! outer_literal {
-    ($line:expr, $start:expr, $stop:expr) => {{
-        if let (Some(start), Some(stop)) = ($line.find($start), $line.rfind($stop)) {
-            $line
-                .chars()
-                .take(start)
-                .chain($line.chars().skip(stop + 1))
-                .collect::<String>()
-        } else {
-            $line.to_string()
-        }
-    }};
-}
-
 impl FromStr for RuntimeType {
     type Err = color_eyre::Report;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.trim().trim_start_matches("T::");
-
-        let runtime_type = match s {
-            "bool" | "usize" | "u8" | "u16" | "u32" | "u64" | "u128" | "isize" | "i8" | "i16"
-            | "i32" | "i64" | "i128" => RuntimeType::Primitive(s.to_string()),
-            _ if match_literal!(s, "Option<", ">") => RuntimeType::Option(Box::new(
-                RuntimeType::from_str(inner_literal!(s, "Option<", ">"))?,
-            )),
-            _ if match_literal!(s, "Vec<", ">") => RuntimeType::Vec(Box::new(
-                RuntimeType::from_str(inner_literal!(s, "Vec<", ">"))?,
-            )),
-            _ if match_literal!(s, "(", ")") => RuntimeType::Tuple(Box::new(
-                inner_literal!(s, "(", ")")
-                    .split(",")
-                    .map(|ty| RuntimeType::from_str(ty))
-                    .collect::<color_eyre::Result<Vec<_>>>()?,
-            )),
-            _ => RuntimeType::Complex(
-                outer_literal!(s, "<", ">")
-                    .trim_start_matches("::")
-                    .to_string(),
-            ),
-        };
-        Ok(runtime_type)
+        Self::parse(s, &Config::default())
     }
 }
@@ -1,44 +1,206 @@
 use argh::FromArgs;
 use color_eyre::eyre::{self, WrapErr};
 use frame_metadata::RuntimeMetadataPrefixed;
-use subxt_gen::decode_metadata;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, TokenStreamExt};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use subxt_gen::{
+    decode_metadata_formatted, decode_modules, fetch_block_hash, fetch_metadata, format_stream,
+    parse_metadata, Config,
+};
 
 #[derive(FromArgs)]
 /// Encode runtime metadata
 struct SubxtGen {
-    /// url of the parachain
+    /// url of the parachain, either http(s) or ws(s)
     #[argh(option, default = "String::from(\"http://localhost:9933\")")]
     url: String,
+
+    /// read metadata from a file instead of the parachain, use - for stdin
+    #[argh(option)]
+    file: Option<String>,
+
+    /// fetch metadata at the given block hash
+    #[argh(option)]
+    at: Option<String>,
+
+    /// fetch metadata at the given block number
+    #[argh(option)]
+    block: Option<u64>,
+
+    /// write the generated code to a file instead of stdout
+    #[argh(option)]
+    out: Option<PathBuf>,
+
+    /// write one file per module and a mod.rs to the given directory
+    #[argh(option)]
+    out_dir: Option<PathBuf>,
+
+    /// only generate pallets matching these comma separated glob patterns,
+    /// this overrides the default exclusion of pallets such as System
+    #[argh(option)]
+    include: Vec<String>,
+
+    /// skip pallets matching these comma separated glob patterns
+    #[argh(option)]
+    exclude: Vec<String>,
+
+    /// only generate these comma separated items, e.g. Tokens::transfer
+    /// or Tokens::*::events
+    #[argh(option)]
+    items: Vec<String>,
+
+    /// map comma separated qualified paths or type names to associated
+    /// types, e.g. StaticLookup::Source=Address
+    #[argh(option)]
+    associated_type: Vec<String>,
+
+    /// toml or json file mapping type names to rust paths,
+    /// e.g. Balance = "u128"
+    #[argh(option)]
+    types: Option<PathBuf>,
+
+    /// additional comma separated associated types provided by the
+    /// System trait of the client
+    #[argh(option)]
+    system_type: Vec<String>,
+
+    /// suffix for names that are rust keywords instead of using raw
+    /// identifiers, e.g. _ to generate type_ for type
+    #[argh(option)]
+    ident_suffix: Option<String>,
+
+    /// skip calls, events and storage items that cannot be generated
+    /// with a warning instead of failing
+    #[argh(switch)]
+    keep_going: bool,
+
+    /// embed the values of primitive constants as consts next to
+    /// the accessors that read them from the node
+    #[argh(switch)]
+    embed_constants: bool,
+
+    /// do not skip the pallets provided by substrate-subxt, such as System,
+    /// even when they are not included explicitly
+    #[argh(switch)]
+    no_default_exclude: bool,
+
+    /// do not map common primitives such as H256 or Perbill to their
+    /// sp_core, sp_runtime and sp_arithmetic paths
+    #[argh(switch)]
+    no_default_types: bool,
+}
+
+fn split_patterns(patterns: Vec<String>) -> Vec<String> {
+    patterns
+        .iter()
+        .flat_map(|patterns| patterns.split(','))
+        .map(|pattern| pattern.trim().to_string())
+        .filter(|pattern| !pattern.is_empty())
+        .collect()
 }
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
     let args: SubxtGen = argh::from_env();
 
-    let metadata = fetch_metadata(&args.url)?;
-    let stream = decode_metadata(metadata)?;
+    let mut config = Config::default();
+    if args.no_default_exclude {
+        config.default_exclude.clear();
+    }
+    config.include = split_patterns(args.include);
+    config.exclude = split_patterns(args.exclude);
+    config.items = split_patterns(args.items);
+    config.system_types.extend(split_patterns(args.system_type));
+    config.ident_suffix = args.ident_suffix;
+    config.keep_going = args.keep_going;
+    config.embed_constants = args.embed_constants;
+    for mapping in split_patterns(args.associated_type) {
+        let (from, to) = mapping
+            .split_once('=')
+            .ok_or_else(|| eyre::eyre!("--associated-type {} should be From=To", mapping))?;
+        config
+            .associated_types
+            .insert(from.trim().to_string(), to.trim().to_string());
+    }
+
+    if args.no_default_types {
+        config.types.clear();
+    }
+    if let Some(path) = args.types {
+        config.read_types(&path)?;
+    }
+
+    let metadata = match (args.file, args.at, args.block) {
+        (Some(path), None, None) => read_metadata(&path)?,
+        (Some(_), _, _) => {
+            return Err(eyre::eyre!("--at and --block cannot be used with --file"));
+        }
+        (None, Some(_), Some(_)) => {
+            return Err(eyre::eyre!("--at and --block cannot be used together"));
+        }
+        (None, at, None) => fetch_metadata(&args.url, at)?,
+        (None, None, Some(number)) => {
+            let at = fetch_block_hash(&args.url, number)?;
+            fetch_metadata(&args.url, Some(at))?
+        }
+    };
+
+    match (args.out, args.out_dir) {
+        (Some(_), Some(_)) => {
+            return Err(eyre::eyre!("--out and --out-dir cannot be used together"));
+        }
+        (Some(path), None) => {
+            let code = decode_metadata_formatted(metadata, &config)?;
+            fs::write(&path, code).with_context(|| format!("error writing {}", path.display()))?;
+        }
+        (None, Some(dir)) => write_modules(&dir, decode_modules(metadata, &config)?)?,
+        (None, None) => {
+            let code = decode_metadata_formatted(metadata, &config)?;
+            print!("{}", code);
+        }
+    }
+
+    Ok(())
+}
+
+fn write_modules(dir: &Path, modules: Vec<(String, TokenStream)>) -> color_eyre::Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("error creating {}", dir.display()))?;
+
+    let mut mod_stream = quote! {
+        #![allow(dead_code)]
+        #![allow(unused_imports)]
+    };
 
-    println!("{}", stream);
+    for (name, module) in modules {
+        // raw identifiers are only valid in code, `mod r#type;` is read from `type.rs`
+        let path = dir.join(format!("{}.rs", name.trim_start_matches("r#")));
+        fs::write(&path, format_stream(module)?)
+            .with_context(|| format!("error writing {}", path.display()))?;
+
+        let ident = format_ident!("{}", name);
+        mod_stream.append_all(quote!(pub mod #ident;));
+    }
+
+    let path = dir.join("mod.rs");
+    fs::write(&path, format_stream(mod_stream)?)
+        .with_context(|| format!("error writing {}", path.display()))?;
 
     Ok(())
 }
 
-fn fetch_metadata(url: &str) -> color_eyre::Result<RuntimeMetadataPrefixed> {
-    let resp = ureq::post(url)
-        .set("Content-Type", "application/json")
-        .send_json(ureq::json!({
-            "jsonrpc": "2.0",
-            "method": "state_getMetadata",
-            "id": 1
-        }))
-        .context("error fetching metadata from the substrate node")?;
-
-    let json: serde_json::Value = resp.into_json()?;
-    let hex_data = json["result"]
-        .as_str()
-        .ok_or(eyre::eyre!("metadata result field should be a string"))?;
-
-    let bytes = hex::decode(hex_data.trim_start_matches("0x"))?;
-    let decoded = scale::Decode::decode(&mut &bytes[..])?;
-    Ok(decoded)
+/// Read metadata stored as raw SCALE bytes, a hex string or a JSON-RPC response.
+fn read_metadata(path: &str) -> color_eyre::Result<RuntimeMetadataPrefixed> {
+    let mut bytes = Vec::new();
+    if path == "-" {
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .context("error reading metadata from stdin")?;
+    } else {
+        bytes = fs::read(path).with_context(|| format!("error reading metadata from {}", path))?;
+    }
+
+    parse_metadata(&bytes)
 }
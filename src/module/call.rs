@@ -1,78 +1,74 @@
-use super::DecodeOrElse;
-use crate::RuntimeType;
-use frame_metadata::v12;
+use super::{doc_attrs, ident, parse_type, FunctionArgumentMetadata, FunctionMetadata};
+use crate::{Config, RuntimeType};
+use color_eyre::eyre::WrapErr;
 use heck::CamelCase;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, TokenStreamExt};
-use std::convert::TryInto;
-use std::str::FromStr;
-
-#[derive(Clone)]
-struct FunctionArgument {
-    name: String,
-    ty: RuntimeType,
-}
-
-impl TryInto<FunctionArgument> for v12::FunctionArgumentMetadata {
-    type Error = color_eyre::Report;
-    fn try_into(self) -> Result<FunctionArgument, Self::Error> {
-        let ty = self.ty.clone().decode_or_else()?;
-        Ok(FunctionArgument {
-            name: self.name.decode_or_else()?,
-            ty: RuntimeType::from_str(ty.as_ref())?,
-        })
-    }
-}
 
 pub fn add_call_to_module(
     module_name: &str,
-    fn_meta: v12::FunctionMetadata,
+    fn_meta: FunctionMetadata,
+    config: &Config,
     stream: &mut TokenStream,
 ) -> color_eyre::Result<Vec<RuntimeType>> {
-    let name = fn_meta.name.decode_or_else()?;
-    let arguments = fn_meta
-        .arguments
-        .decode_or_else()?
-        .into_iter()
-        .map(TryInto::try_into)
-        .collect::<color_eyre::Result<Vec<FunctionArgument>>>()?;
+    let name = fn_meta.name;
+
+    let arguments = fn_meta.arguments;
+    let struct_docs = doc_attrs(&fn_meta.docs, None);
+    let trait_docs = doc_attrs(&fn_meta.docs, Some(" Call extension trait."));
+    let call_docs = doc_attrs(&fn_meta.docs, Some(" Create and submit an extrinsic."));
 
     let subxt = format_ident!("substrate_subxt");
 
-    let module = format_ident!("{}", module_name);
+    let module = ident(module_name, config);
     let call_name = name.clone();
-    let call_struct = format_ident!("{}Call", call_name.to_camel_case());
-    let call_trait = format_ident!("{}CallExt", call_name.to_camel_case());
-    let call = format_ident!("{}", call_name);
-    let call_and_watch = format_ident!("{}_and_watch", call_name);
+    let call_struct = ident(&format!("{}Call", call_name.to_camel_case()), config);
+    let call_trait = ident(&format!("{}CallExt", call_name.to_camel_case()), config);
+    let call = ident(&call_name, config);
+    let call_and_watch = ident(&format!("{}_and_watch", call_name), config);
 
     let fields = arguments
         .clone()
         .into_iter()
-        .map(|FunctionArgument { name, ty }| {
-            (
-                format_ident!("{}", name),
-                syn::parse_str::<syn::Type>(&format!("{}", ty)).unwrap(),
-            )
+        .map(|FunctionArgumentMetadata { name, ty }| {
+            // the call is encoded compactly, callers pass the plain value
+            let (compact, ty) = match ty {
+                RuntimeType::Compact(ty) => (true, *ty),
+                ty => (false, ty),
+            };
+            let ty = parse_type(&ty).with_context(|| {
+                format!(
+                    "error generating argument `{}` of call `{}` in pallet `{}`",
+                    name, call_name, module_name
+                )
+            })?;
+            Ok((ident(&name, config), ty, compact))
         })
-        .collect::<Vec<_>>();
+        .collect::<color_eyre::Result<Vec<_>>>()?;
 
-    let call_args = fields.iter().map(|(field, ty)| quote!(#field: #ty,));
+    let call_args = fields.iter().map(|(field, ty, _)| quote!(#field: #ty,));
     let call_args = quote!(#(#call_args)*);
 
     let call_fields = fields
         .iter()
-        .map(|(name, ty)| quote! { #name: #ty })
+        .map(|(name, ty, compact)| {
+            if *compact {
+                quote! { #[codec(compact)] pub #name: #ty }
+            } else {
+                quote! { pub #name: #ty }
+            }
+        })
         .collect::<Vec<_>>();
 
-    let call_init = fields.iter().map(|(field, _)| quote!(#field,));
+    let call_init = fields.iter().map(|(field, _, _)| quote!(#field,));
     let call_init = quote!(#(#call_init)*);
 
     stream.append_all(quote! {
+        #struct_docs
         #[derive(Encode)]
         pub struct #call_struct<T: #module + #subxt::Runtime> {
             pub _marker: core::marker::PhantomData<T>,
-            #(pub #call_fields,)*
+            #(#call_fields,)*
         }
 
         impl<T: #module + #subxt::Runtime> #subxt::Call<T> for #call_struct<T> {
@@ -80,8 +76,9 @@ pub fn add_call_to_module(
             const FUNCTION: &'static str = #call_name;
         }
 
+        #trait_docs
         pub trait #call_trait<T: #module + #subxt::Runtime> {
-            /// Create and submit an extrinsic.
+            #call_docs
             fn #call<'a>(
                 &'a self,
                 signer: &'a (dyn #subxt::Signer<T> + Send + Sync),
@@ -0,0 +1,91 @@
+use super::{doc_attrs, ident, parse_type, ConstantMetadata};
+use crate::{Config, RuntimeType};
+use color_eyre::eyre::WrapErr;
+use heck::{CamelCase, ShoutySnakeCase, SnakeCase};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote, TokenStreamExt};
+use scale::Decode;
+
+/// Decode the value of a primitive constant into a literal.
+fn literal(ty: &RuntimeType, value: &[u8]) -> color_eyre::Result<Option<TokenStream>> {
+    fn decode<T: Decode>(mut value: &[u8]) -> color_eyre::Result<T> {
+        T::decode(&mut value).wrap_err("error decoding the value")
+    }
+
+    let literal = match ty.to_string().as_str() {
+        "bool" if decode::<bool>(value)? => return Ok(Some(quote!(true))),
+        "bool" => return Ok(Some(quote!(false))),
+        "u8" => Literal::u8_unsuffixed(decode(value)?),
+        "u16" => Literal::u16_unsuffixed(decode(value)?),
+        "u32" => Literal::u32_unsuffixed(decode(value)?),
+        "u64" => Literal::u64_unsuffixed(decode(value)?),
+        "u128" => Literal::u128_unsuffixed(decode(value)?),
+        "i8" => Literal::i8_unsuffixed(decode(value)?),
+        "i16" => Literal::i16_unsuffixed(decode(value)?),
+        "i32" => Literal::i32_unsuffixed(decode(value)?),
+        "i64" => Literal::i64_unsuffixed(decode(value)?),
+        "i128" => Literal::i128_unsuffixed(decode(value)?),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(quote!(#literal)))
+}
+
+pub fn add_constant_to_module(
+    module_name: &str,
+    constant_meta: ConstantMetadata,
+    config: &Config,
+    stream: &mut TokenStream,
+) -> color_eyre::Result<Vec<RuntimeType>> {
+    let name = constant_meta.name;
+
+    let ty = constant_meta.ty;
+    let trait_docs = doc_attrs(&constant_meta.docs, Some(" Constant extension trait."));
+    let constant_docs = doc_attrs(
+        &constant_meta.docs,
+        Some(" Reads the constant from the metadata of the connected node."),
+    );
+    let context = || {
+        format!(
+            "error generating constant `{}` in pallet `{}`",
+            name, module_name
+        )
+    };
+
+    let subxt = format_ident!("substrate_subxt");
+
+    let module = ident(module_name, config);
+    let constant_name = name.clone();
+    let constant = ident(&constant_name.to_snake_case(), config);
+    let constant_trait = ident(
+        &format!("{}ConstantExt", constant_name.to_camel_case()),
+        config,
+    );
+    let constant_ty = parse_type(&ty).with_context(context)?;
+
+    stream.append_all(quote! {
+        #trait_docs
+        pub trait #constant_trait<T: #module + #subxt::Runtime> {
+            #constant_docs
+            fn #constant(&self) -> Result<#constant_ty, #subxt::MetadataError>;
+        }
+
+        impl<T: #module + #subxt::Runtime> #constant_trait<T> for #subxt::Client<T> {
+            fn #constant(&self) -> Result<#constant_ty, #subxt::MetadataError> {
+                self.metadata().module(MODULE)?.constant(#constant_name)?.value()
+            }
+        }
+    });
+
+    if config.embed_constants {
+        if let Some(value) = literal(&ty, &constant_meta.value).with_context(context)? {
+            let constant = ident(&constant_name.to_shouty_snake_case(), config);
+            stream.append_all(quote! {
+                /// Value of the constant in the metadata the code was generated from.
+                pub const #constant: #constant_ty = #value;
+            });
+        }
+    }
+
+    Ok(vec![ty])
+}
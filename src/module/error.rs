@@ -0,0 +1,72 @@
+use super::{doc_attrs, ident, ErrorMetadata};
+use crate::Config;
+use proc_macro2::{Literal, TokenStream};
+use quote::{quote, TokenStreamExt};
+
+/// Add the errors of a pallet as an `Error` enum, returns whether
+/// there were any errors to add.
+pub fn add_errors_to_module(
+    mut errors: Vec<ErrorMetadata>,
+    config: &Config,
+    stream: &mut TokenStream,
+) -> bool {
+    errors.sort_by_key(|error| error.index);
+
+    if errors.is_empty() {
+        return false;
+    }
+
+    let variants = errors
+        .iter()
+        .map(|error| {
+            let variant = ident(&error.name, config);
+            let index = Literal::u8_unsuffixed(error.index);
+            let docs = doc_attrs(&error.docs, None);
+            quote! {
+                #docs
+                #[codec(index = #index)]
+                #variant
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let messages = errors
+        .iter()
+        .map(|error| {
+            let variant = ident(&error.name, config);
+            let message = error
+                .docs
+                .iter()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let message = if message.is_empty() {
+                error.name.clone()
+            } else {
+                message
+            };
+            quote!(Self::#variant => #message)
+        })
+        .collect::<Vec<_>>();
+
+    stream.append_all(quote! {
+        /// Errors of the pallet.
+        #[derive(Debug, Decode)]
+        pub enum Error {
+            #(#variants,)*
+        }
+
+        impl core::fmt::Display for Error {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str(match self {
+                    #(#messages,)*
+                })
+            }
+        }
+
+        impl std::error::Error for Error {}
+    });
+
+    true
+}
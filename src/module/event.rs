@@ -1,39 +1,47 @@
-use super::DecodeOrElse;
-use crate::RuntimeType;
-use frame_metadata::v12;
+use super::{doc_attrs, ident, parse_type, EventMetadata};
+use crate::{Config, RuntimeType};
+use color_eyre::eyre::WrapErr;
 use heck::{CamelCase, SnakeCase};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, TokenStreamExt};
-use std::str::FromStr;
 
 pub fn add_event_to_module(
     module_name: &str,
-    event_meta: v12::EventMetadata,
+    event_meta: EventMetadata,
+    config: &Config,
     stream: &mut TokenStream,
 ) -> color_eyre::Result<Vec<RuntimeType>> {
-    let name = event_meta.name.decode_or_else()?;
-    let arguments = event_meta.arguments.decode_or_else()?;
-    let arguments = arguments
-        .into_iter()
-        .map(|arg| RuntimeType::from_str(&arg))
-        .collect::<color_eyre::Result<Vec<_>>>()?;
+    let name = event_meta.name;
+
+    let arguments = event_meta.arguments;
+    let struct_docs = doc_attrs(&event_meta.docs, None);
+    let trait_docs = doc_attrs(&event_meta.docs, Some(" Event extension trait."));
+    let event_docs = doc_attrs(&event_meta.docs, Some(" Retrieves the event."));
 
     let subxt = format_ident!("substrate_subxt");
     let codec = format_ident!("parity_scale_codec");
 
-    let module = format_ident!("{}", module_name);
+    let module = ident(module_name, config);
     let event_name = name.clone();
-    let event = format_ident!("{}", event_name.to_snake_case());
-    let event_struct = format_ident!("{}Event", event_name.to_camel_case());
-    let event_trait = format_ident!("{}EventExt", event_name);
+    let event = ident(&event_name.to_snake_case(), config);
+    let event_struct = ident(&format!("{}Event", event_name.to_camel_case()), config);
+    let event_trait = ident(&format!("{}EventExt", event_name), config);
 
     let event_fields = arguments
-        .clone()
-        .into_iter()
-        .map(|ty| syn::parse_str::<syn::Type>(&format!("{}", ty)).unwrap())
-        .collect::<Vec<_>>();
+        .iter()
+        .enumerate()
+        .map(|(i, ty)| {
+            parse_type(ty).with_context(|| {
+                format!(
+                    "error generating argument {} of event `{}` in pallet `{}`",
+                    i, event_name, module_name
+                )
+            })
+        })
+        .collect::<color_eyre::Result<Vec<_>>>()?;
 
     stream.append_all(quote! {
+        #struct_docs
         #[derive(Decode)]
         pub struct #event_struct<T: #module> (
             pub core::marker::PhantomData<T>,
@@ -45,9 +53,9 @@ pub fn add_event_to_module(
             const EVENT: &'static str = #event_name;
         }
 
-        /// Event extension trait.
+        #trait_docs
         pub trait #event_trait<T: #module> {
-            /// Retrieves the event.
+            #event_docs
             fn #event(&self) -> Result<Option<#event_struct<T>>, #codec::Error>;
         }
 
@@ -0,0 +1,415 @@
+use crate::{Config, RuntimeType};
+use color_eyre::eyre;
+use heck::{CamelCase, SnakeCase};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+use std::collections::BTreeMap;
+
+mod call;
+mod constant;
+mod error;
+mod event;
+mod store;
+
+use call::add_call_to_module;
+use constant::add_constant_to_module;
+use error::add_errors_to_module;
+use event::add_event_to_module;
+use store::add_storage_to_module;
+
+/// Version independent view of a runtime module, each
+/// metadata backend decodes into this before generating code.
+pub struct ModuleMetadata {
+    pub name: String,
+    /// Index of the module in the runtime, used by dispatch errors.
+    pub index: u8,
+    pub calls: Vec<FunctionMetadata>,
+    pub events: Vec<EventMetadata>,
+    pub storage: Vec<StorageEntryMetadata>,
+    pub constants: Vec<ConstantMetadata>,
+    pub errors: Vec<ErrorMetadata>,
+}
+
+pub struct FunctionMetadata {
+    pub name: String,
+    pub arguments: Vec<FunctionArgumentMetadata>,
+    pub docs: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct FunctionArgumentMetadata {
+    pub name: String,
+    pub ty: RuntimeType,
+}
+
+pub struct EventMetadata {
+    pub name: String,
+    pub arguments: Vec<RuntimeType>,
+    pub docs: Vec<String>,
+}
+
+pub struct StorageEntryMetadata {
+    pub name: String,
+    pub ty: StorageEntryType,
+    pub docs: Vec<String>,
+}
+
+pub enum StorageEntryType {
+    Plain(RuntimeType),
+    Map {
+        key: RuntimeType,
+        value: RuntimeType,
+    },
+    DoubleMap {
+        key1: RuntimeType,
+        key2: RuntimeType,
+        value: RuntimeType,
+    },
+    NMap {
+        keys: Vec<RuntimeType>,
+        hashers: Vec<StorageHasher>,
+        value: RuntimeType,
+    },
+}
+
+pub struct ConstantMetadata {
+    pub name: String,
+    pub ty: RuntimeType,
+    /// SCALE encoded value.
+    pub value: Vec<u8>,
+    pub docs: Vec<String>,
+}
+
+pub struct ErrorMetadata {
+    pub name: String,
+    /// Index of the error within the module.
+    pub index: u8,
+    pub docs: Vec<String>,
+}
+
+#[derive(Clone, Copy)]
+pub enum StorageHasher {
+    Blake2_128,
+    Blake2_256,
+    Blake2_128Concat,
+    Twox128,
+    Twox256,
+    Twox64Concat,
+    Identity,
+}
+
+/// Keywords that cannot be used as raw identifiers.
+const NON_RAW_KEYWORDS: &[&str] = &["self", "Self", "super", "crate", "_"];
+
+/// Turn a name from the metadata into an identifier, invalid characters
+/// become underscores and keywords become raw identifiers unless a
+/// suffix is configured, e.g. `type` becomes `r#type` or `type_`.
+pub(crate) fn ident(name: &str, config: &Config) -> Ident {
+    let mut name = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if name.is_empty() || name.starts_with(|c: char| c.is_numeric()) {
+        name.insert(0, '_');
+    }
+
+    // keywords are rejected by the parser
+    if syn::parse_str::<Ident>(&name).is_ok() {
+        return format_ident!("{}", name);
+    }
+
+    match &config.ident_suffix {
+        None if !NON_RAW_KEYWORDS.contains(&name.as_str()) => {
+            Ident::new_raw(&name, Span::call_site())
+        }
+        suffix => format_ident!("{}{}", name, suffix.as_deref().unwrap_or("_")),
+    }
+}
+
+/// Doc attributes for the docs of an item in the metadata, or
+/// for the given default if it has none.
+fn doc_attrs(docs: &[String], default: Option<&str>) -> TokenStream {
+    match default {
+        Some(default) if docs.is_empty() => quote!(#[doc = #default]),
+        _ => quote!(#(#[doc = #docs])*),
+    }
+}
+
+/// Parse a runtime type into the Rust type used by the generated code.
+pub(crate) fn parse_type(ty: &RuntimeType) -> color_eyre::Result<syn::Type> {
+    let ty = ty.to_string();
+    syn::parse_str(&ty).map_err(|err| eyre::eyre!("`{}` is not a valid type: {}", ty, err))
+}
+
+/// Name of the generated module holding the associated types
+/// used by more than one pallet.
+pub const SHARED_MODULE: &str = "shared_types";
+
+/// Name of the generated module mapping dispatch errors to
+/// the errors of the pallets.
+pub const ERRORS_MODULE: &str = "errors";
+
+/// Pallet modules whose traits are only generated once every pallet
+/// is known, so that associated types used by several pallets can be
+/// declared once in a shared supertrait.
+#[derive(Default)]
+pub struct Modules {
+    pallets: Vec<Pallet>,
+}
+
+struct Pallet {
+    name: String,
+    index: u8,
+    /// Whether the pallet module declares an `Error` enum.
+    has_errors: bool,
+    /// Associated types sorted by name so the generated code is stable.
+    assoc_tys: BTreeMap<String, AssocTy>,
+    stream: TokenStream,
+}
+
+#[derive(Clone, Copy, Default)]
+struct AssocTy {
+    /// Number of generic parameters the type takes.
+    params: usize,
+    /// Whether the type is used inside `Compact<..>`.
+    compact: bool,
+}
+
+/// A type used with and without parameters is declared with them,
+/// and as compact if any use is.
+fn merge_assoc_ty(assoc_tys: &mut BTreeMap<String, AssocTy>, name: String, ty: AssocTy) {
+    let entry = assoc_tys.entry(name).or_default();
+    entry.params = ty.params.max(entry.params);
+    entry.compact |= ty.compact;
+}
+
+fn assoc_ty_decls(assoc_tys: &BTreeMap<String, AssocTy>, config: &Config) -> Vec<TokenStream> {
+    assoc_tys
+        .iter()
+        .map(|(rt, ty)| {
+            let rt = ident(rt, config);
+            let bounds = quote!(Codec + EncodeLike + Member + Default + Send + Sync);
+            // the runtime can only derive the bounds of the associated
+            // type for its own generic type if its parameters have them
+            let generics = if ty.params == 0 {
+                quote!()
+            } else {
+                let params = (0..ty.params).map(|i| format_ident!("A{}", i));
+                quote!(<#(#params: #bounds),*>)
+            };
+            // `#[codec(compact)]` fields need the type to have a compact encoding
+            let compact = if ty.compact {
+                quote!(+ parity_scale_codec::HasCompact)
+            } else {
+                quote!()
+            };
+            quote!(type #rt #generics: #bounds #compact;)
+        })
+        .collect()
+}
+
+impl Modules {
+    /// Generate the contents of each module, keyed by the module name.
+    pub fn generate(self, config: &Config) -> Vec<(String, TokenStream)> {
+        let mut uses = BTreeMap::<&str, usize>::new();
+        for pallet in &self.pallets {
+            for name in pallet.assoc_tys.keys() {
+                *uses.entry(name).or_default() += 1;
+            }
+        }
+
+        let mut shared_tys = BTreeMap::new();
+        for pallet in &self.pallets {
+            for (name, ty) in &pallet.assoc_tys {
+                if uses[name.as_str()] > 1 {
+                    merge_assoc_ty(&mut shared_tys, name.clone(), *ty);
+                }
+            }
+        }
+
+        let shared_trait = format_ident!("{}", SHARED_MODULE.to_camel_case());
+        let shared_module = format_ident!("{}", SHARED_MODULE);
+        let mut modules = Vec::new();
+
+        if !shared_tys.is_empty() {
+            let assoc_tys = assoc_ty_decls(&shared_tys, config);
+            modules.push((
+                SHARED_MODULE.to_string(),
+                quote! {
+                    use parity_scale_codec::{Codec, EncodeLike};
+                    use sp_runtime::traits::Member;
+
+                    pub trait #shared_trait: substrate_subxt::system::System {
+                        #(#assoc_tys)*
+                    }
+                },
+            ));
+        }
+
+        let errors = self
+            .pallets
+            .iter()
+            .filter(|pallet| pallet.has_errors)
+            .map(|pallet| (pallet.name.clone(), pallet.index))
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            modules.push((ERRORS_MODULE.to_string(), generate_errors(&errors, config)));
+        }
+
+        for Pallet {
+            name,
+            mut assoc_tys,
+            stream,
+            ..
+        } in self.pallets
+        {
+            let len = assoc_tys.len();
+            assoc_tys.retain(|name, _| !shared_tys.contains_key(name));
+            let supertrait = if assoc_tys.len() < len {
+                quote!(super::#shared_module::#shared_trait)
+            } else {
+                quote!(substrate_subxt::system::System)
+            };
+
+            let assoc_tys = assoc_ty_decls(&assoc_tys, config);
+            let module_trait = ident(&name, config);
+            let module = ident(&name.to_snake_case(), config);
+
+            let stream = quote! {
+                use parity_scale_codec::{Codec, EncodeLike, Encode, Decode};
+                use sp_runtime::traits::Member;
+
+                const MODULE: &str = #name;
+
+                pub trait #module_trait: #supertrait {
+                    #(#assoc_tys)*
+                }
+
+                #stream
+            };
+
+            modules.push((module.to_string(), stream));
+        }
+
+        modules
+    }
+}
+
+/// Generate the error of any pallet that has errors, which can be
+/// looked up from the indices of a `DispatchError::Module`.
+fn generate_errors(pallets: &[(String, u8)], config: &Config) -> TokenStream {
+    let variants = pallets
+        .iter()
+        .map(|(name, _)| ident(name, config))
+        .collect::<Vec<_>>();
+    let modules = pallets
+        .iter()
+        .map(|(name, _)| ident(&name.to_snake_case(), config))
+        .collect::<Vec<_>>();
+    let names = pallets.iter().map(|(name, _)| name);
+    let indices = pallets
+        .iter()
+        .map(|(_, index)| Literal::u8_unsuffixed(*index));
+
+    quote! {
+        use parity_scale_codec::Decode;
+
+        /// Error of one of the pallets.
+        #[derive(Debug)]
+        pub enum ModuleError {
+            #(#variants(super::#modules::Error),)*
+        }
+
+        impl ModuleError {
+            /// The error of the pallet with the given index, `None` if the
+            /// pallet or its error is unknown.
+            pub fn from_indices(index: u8, error: u8) -> Option<Self> {
+                let error = &mut &[error][..];
+                match index {
+                    #(#indices => super::#modules::Error::decode(error).ok().map(Self::#variants),)*
+                    _ => None,
+                }
+            }
+
+            /// The pallet error of a `DispatchError::Module`, `None` for
+            /// other dispatch errors.
+            pub fn from_dispatch_error(error: &sp_runtime::DispatchError) -> Option<Self> {
+                match *error {
+                    sp_runtime::DispatchError::Module { index, error, .. } => {
+                        Self::from_indices(index, error)
+                    }
+                    _ => None,
+                }
+            }
+        }
+
+        impl core::fmt::Display for ModuleError {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    #(Self::#variants(error) => write!(f, "{}::{:?}: {}", #names, error, error),)*
+                }
+            }
+        }
+
+        impl std::error::Error for ModuleError {}
+    }
+}
+
+pub fn add_module(
+    module: ModuleMetadata,
+    config: &Config,
+    modules: &mut Modules,
+) -> color_eyre::Result<()> {
+    let module_name = module.name.to_camel_case();
+
+    let mut runtime_types = Vec::new();
+    let mut module_stream = TokenStream::new();
+
+    // items only add to the stream once they are generated successfully
+    // so failed ones can be skipped
+    for call in module.calls {
+        let result = add_call_to_module(&module_name, call, config, &mut module_stream);
+        runtime_types.extend(config.recover(result)?.unwrap_or_default());
+    }
+
+    for event in module.events {
+        let result = add_event_to_module(&module_name, event, config, &mut module_stream);
+        runtime_types.extend(config.recover(result)?.unwrap_or_default());
+    }
+
+    for entry in module.storage {
+        let result = add_storage_to_module(&module_name, entry, config, &mut module_stream);
+        runtime_types.extend(config.recover(result)?.unwrap_or_default());
+    }
+
+    for constant in module.constants {
+        let result = add_constant_to_module(&module_name, constant, config, &mut module_stream);
+        runtime_types.extend(config.recover(result)?.unwrap_or_default());
+    }
+
+    let has_errors = add_errors_to_module(module.errors, config, &mut module_stream);
+
+    let mut assoc_tys = BTreeMap::new();
+    for (name, params, compact) in runtime_types
+        .into_iter()
+        .flat_map(|rt| rt.flatten_complex().into_iter())
+    {
+        merge_assoc_ty(&mut assoc_tys, name, AssocTy { params, compact });
+    }
+
+    modules.pallets.push(Pallet {
+        name: module_name,
+        index: module.index,
+        has_errors,
+        assoc_tys,
+        stream: module_stream,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use super::{doc_attrs, ident, parse_type, StorageEntryMetadata, StorageEntryType, StorageHasher};
+use crate::{Config, RuntimeType};
+use color_eyre::eyre::WrapErr;
+use heck::{CamelCase, SnakeCase};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, TokenStreamExt};
+
+fn hash_key(hasher: StorageHasher, index: usize) -> TokenStream {
+    let subxt = format_ident!("substrate_subxt");
+    let field = syn::Index::from(index);
+    let hashing = quote!(#subxt::sp_core::hashing);
+    match hasher {
+        StorageHasher::Blake2_128 => quote!(#hashing::blake2_128(&self.#field.encode()).to_vec()),
+        StorageHasher::Blake2_256 => quote!(#hashing::blake2_256(&self.#field.encode()).to_vec()),
+        StorageHasher::Blake2_128Concat => quote! {{
+            let encoded = self.#field.encode();
+            #hashing::blake2_128(&encoded).iter().chain(encoded.iter()).cloned().collect::<Vec<u8>>()
+        }},
+        StorageHasher::Twox128 => quote!(#hashing::twox_128(&self.#field.encode()).to_vec()),
+        StorageHasher::Twox256 => quote!(#hashing::twox_256(&self.#field.encode()).to_vec()),
+        StorageHasher::Twox64Concat => quote! {{
+            let encoded = self.#field.encode();
+            #hashing::twox_64(&encoded).iter().chain(encoded.iter()).cloned().collect::<Vec<u8>>()
+        }},
+        StorageHasher::Identity => quote!(self.#field.encode()),
+    }
+}
+
+pub fn add_storage_to_module(
+    module_name: &str,
+    entry: StorageEntryMetadata,
+    config: &Config,
+    stream: &mut TokenStream,
+) -> color_eyre::Result<Vec<RuntimeType>> {
+    let name = entry.name;
+
+    let struct_docs = doc_attrs(&entry.docs, None);
+    let trait_docs = doc_attrs(&entry.docs, Some(" Store extension trait."));
+    let store_docs = doc_attrs(&entry.docs, Some(" Retrieve the store element."));
+
+    let mut runtime_types = Vec::new();
+
+    let mut parse_storage_ty = |ty: RuntimeType, part: &str| -> color_eyre::Result<_> {
+        runtime_types.push(ty.clone());
+        parse_type(&ty).with_context(|| {
+            format!(
+                "error generating {} of storage `{}` in pallet `{}`",
+                part, name, module_name
+            )
+        })
+    };
+
+    let (key_tys, hashers, ret_ty) = match entry.ty {
+        StorageEntryType::Plain(plain) => (vec![], None, plain),
+        StorageEntryType::Map { key, value } => (vec![key], None, value),
+        StorageEntryType::DoubleMap { key1, key2, value } => (vec![key1, key2], None, value),
+        StorageEntryType::NMap {
+            keys,
+            hashers,
+            value,
+        } => (keys, Some(hashers), value),
+    };
+
+    let ret_ty = parse_storage_ty(ret_ty, "value")?;
+
+    let key_tys = key_tys
+        .into_iter()
+        .enumerate()
+        .map(|(i, ty)| parse_storage_ty(ty, &format!("key {}", i + 1)))
+        .collect::<color_eyre::Result<Vec<_>>>()?;
+
+    let key_names = if key_tys.len() == 1 {
+        vec![format_ident!("key")]
+    } else {
+        (1..=key_tys.len())
+            .map(|i| format_ident!("key{}", i))
+            .collect()
+    };
+
+    let key_ty = quote!(#(#key_tys,)*);
+    let key_arg = quote!(#(#key_names: #key_tys,)*);
+    let key_our = quote!(#(#key_names),*);
+
+    let subxt = format_ident!("substrate_subxt");
+
+    let store_key = match hashers {
+        // n-map keys are not understood by the client metadata so we
+        // hash each key part according to the hashers we were given
+        Some(hashers) => {
+            let hashed_keys = hashers
+                .into_iter()
+                .enumerate()
+                .map(|(i, hasher)| hash_key(hasher, i + 1));
+            quote! {
+                let mut bytes = Self::prefix(metadata)?.0;
+                #(bytes.extend(#hashed_keys);)*
+                Ok(#subxt::sp_core::storage::StorageKey(bytes))
+            }
+        }
+        None => {
+            let store_ty = format_ident!(
+                "{}",
+                match key_tys.len() {
+                    0 => "plain",
+                    1 => "map",
+                    _ => "double_map",
+                }
+            );
+            let key_acc = (1..=key_tys.len()).map(syn::Index::from);
+            quote! {
+                Ok(metadata
+                    .module(Self::MODULE)?
+                    .storage(Self::FIELD)?
+                    .#store_ty()?
+                    .key(#(&self.#key_acc),*))
+            }
+        }
+    };
+
+    let fetch = quote!(fetch_or_default);
+
+    let module = ident(module_name, config);
+    let store_name = name.clone();
+    let store = ident(&store_name.to_snake_case(), config);
+    let store_iter = ident(&format!("{}_iter", store_name.to_snake_case()), config);
+    let store_struct = ident(&format!("{}Store", store_name.to_camel_case()), config);
+    let store_trait = ident(&format!("{}StoreExt", store_name), config);
+
+    stream.append_all(quote! {
+        #struct_docs
+        #[derive(Encode, Decode)]
+        pub struct #store_struct<T: #module> (
+            core::marker::PhantomData<T>,
+            #key_ty
+        );
+
+        impl<T: #module> #subxt::Store<T> for #store_struct<T> {
+            const MODULE: &'static str = MODULE;
+            const FIELD: &'static str = #store_name;
+
+            type Returns = #ret_ty;
+
+            fn prefix(
+                metadata: &#subxt::Metadata,
+            ) -> Result<#subxt::sp_core::storage::StorageKey, #subxt::MetadataError> {
+                Ok(metadata
+                    .module(Self::MODULE)?
+                    .storage(Self::FIELD)?
+                    .prefix())
+            }
+
+            fn key(
+                &self,
+                metadata: &#subxt::Metadata,
+            ) -> Result<#subxt::sp_core::storage::StorageKey, #subxt::MetadataError> {
+                #store_key
+            }
+        }
+
+        #trait_docs
+        pub trait #store_trait<T: #module + #subxt::Runtime> {
+            #store_docs
+            fn #store<'a>(
+                &'a self,
+                #key_arg
+                hash: Option<T::Hash>,
+            ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<#ret_ty, #subxt::Error>> + Send + 'a>>;
+
+            /// Iterate over the store element.
+            fn #store_iter<'a>(
+                &'a self,
+                hash: Option<T::Hash>,
+            ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<#subxt::KeyIter<T, #store_struct<T>>, #subxt::Error>> + Send + 'a>>;
+        }
+
+        impl<T: #module + #subxt::Runtime> #store_trait<T> for #subxt::Client<T> {
+            fn #store<'a>(
+                &'a self,
+                #key_arg
+                hash: Option<T::Hash>,
+            ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<#ret_ty, #subxt::Error>> + Send + 'a>> {
+                let _marker = core::marker::PhantomData::<T>;
+                Box::pin(async move { self.#fetch(&#store_struct(_marker, #key_our), hash).await })
+            }
+
+            fn #store_iter<'a>(
+                &'a self,
+                hash: Option<T::Hash>,
+            ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<#subxt::KeyIter<T, #store_struct<T>>, #subxt::Error>> + Send + 'a>> {
+                Box::pin(self.iter(hash))
+            }
+        }
+
+    });
+
+    Ok(runtime_types)
+}
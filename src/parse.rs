@@ -0,0 +1,532 @@
+use crate::{Config, RuntimeType};
+use color_eyre::eyre;
+
+/// Parse a type as found in the string based metadata into a [`RuntimeType`].
+pub fn parse_type(input: &str, config: &Config) -> color_eyre::Result<RuntimeType> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        input,
+        tokens: &tokens,
+        pos: 0,
+    };
+    let ty = parser.parse_type()?;
+    match parser.peek() {
+        None => Ok(ty.into_runtime_type(config)),
+        Some(token) => Err(parser.error_at(token, "expected end of type")),
+    }
+}
+
+/// Syntax tree of a type, mirrors the subset of Rust types used in metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Type {
+    Path(Path),
+    Tuple(Vec<Type>),
+    /// `[ty; len]`
+    Array(Box<Type>, usize),
+    /// `[ty]`, only found behind pointers such as `Cow`
+    Slice(Box<Type>),
+    /// `<ty as trait_>::item`
+    Qualified {
+        ty: Box<Type>,
+        trait_: Path,
+        item: Vec<Segment>,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Path {
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Segment {
+    ident: String,
+    args: Vec<Type>,
+}
+
+impl Type {
+    fn into_runtime_type(self, config: &Config) -> RuntimeType {
+        match self {
+            Self::Path(path) => path.into_runtime_type(config),
+            Self::Tuple(fields) => RuntimeType::Tuple(Box::new(
+                fields
+                    .into_iter()
+                    .map(|ty| ty.into_runtime_type(config))
+                    .collect(),
+            )),
+            Self::Array(ty, len) => RuntimeType::Array(Box::new(ty.into_runtime_type(config)), len),
+            Self::Slice(ty) => RuntimeType::Vec(Box::new(ty.into_runtime_type(config))),
+            Self::Qualified {
+                trait_, mut item, ..
+            } => {
+                // the trait only selects where the type is defined, the
+                // runtime provides it as an associated type all the same
+                let trait_name = &trait_.segments[trait_.segments.len() - 1].ident;
+                let last = item.len() - 1;
+                let qualified = format!("{}::{}", trait_name, item[last].ident);
+                if let Some(ty) = config.associated_types.get(&qualified) {
+                    item[last].ident = ty.clone();
+                }
+                Path { segments: item }.into_runtime_type(config)
+            }
+        }
+    }
+}
+
+impl Type {
+    /// Whether this is a generic parameter of the pallet such as the `T`
+    /// in `BalanceOf<T>`, these do not name a type of their own.
+    fn is_generic_param(&self) -> bool {
+        match self {
+            Self::Path(Path { segments }) => match segments.as_slice() {
+                [Segment { ident, args }] => {
+                    args.is_empty()
+                        && ident.len() == 1
+                        && ident.starts_with(|c: char| c.is_ascii_uppercase())
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Path {
+    fn into_runtime_type(mut self, config: &Config) -> RuntimeType {
+        // parsed paths always have at least one segment
+        let Segment { ident, args } = self.segments.pop().unwrap();
+        let ident = config
+            .associated_types
+            .get(&ident)
+            .cloned()
+            .unwrap_or(ident);
+
+        if let Some(path) = config.types.get(&ident) {
+            return user_type(RuntimeType::Path(path.clone()), args, config);
+        }
+
+        // only called for a single argument
+        let inner = |args: Vec<Type>| Box::new(args[0].clone().into_runtime_type(config));
+
+        match (ident.as_str(), args.len()) {
+            (
+                "bool" | "usize" | "u8" | "u16" | "u32" | "u64" | "u128" | "isize" | "i8" | "i16"
+                | "i32" | "i64" | "i128",
+                0,
+            ) => RuntimeType::Primitive(ident),
+            ("Option", 1) => RuntimeType::Option(inner(args)),
+            ("Vec", 1) => RuntimeType::Vec(inner(args)),
+            ("Compact", 1) => RuntimeType::Compact(inner(args)),
+            // provided by the system module without parameters
+            (_, _) if config.system_types.contains(&ident) => RuntimeType::System(ident),
+            // generated types own their data
+            ("Cow", 1) => *inner(args),
+            ("Box", 1) | ("Result", 2) => generic(RuntimeType::Path(ident), args, config),
+            ("BTreeMap", 2) | ("BTreeSet", 1) => generic(
+                RuntimeType::Path(format!("std::collections::{}", ident)),
+                args,
+                config,
+            ),
+            _ => user_type(RuntimeType::Complex(ident), args, config),
+        }
+    }
+}
+
+/// A type named by the runtime, the generic parameters of the pallet are
+/// dropped from its arguments as they are already part of the module trait.
+fn user_type(base: RuntimeType, mut args: Vec<Type>, config: &Config) -> RuntimeType {
+    args.retain(|arg| !arg.is_generic_param());
+    if args.is_empty() {
+        base
+    } else {
+        generic(base, args, config)
+    }
+}
+
+fn generic(base: RuntimeType, args: Vec<Type>, config: &Config) -> RuntimeType {
+    RuntimeType::Generic(
+        Box::new(base),
+        args.into_iter()
+            .map(|ty| ty.into_runtime_type(config))
+            .collect(),
+    )
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TokenKind {
+    Ident,
+    Lifetime,
+    PathSep,
+    Lt,
+    Gt,
+    OpenParen,
+    CloseParen,
+    Comma,
+    OpenBracket,
+    CloseBracket,
+    Semi,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    /// Byte offset into the input.
+    offset: usize,
+}
+
+fn tokenize(input: &str) -> color_eyre::Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        let kind = match c {
+            _ if c.is_whitespace() => continue,
+            _ if c.is_alphanumeric() || c == '_' || c == '\'' => {
+                let mut end = offset + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let kind = match c {
+                    '\'' if end == offset + 1 => {
+                        return Err(error(input, offset, "expected a lifetime"))
+                    }
+                    '\'' => TokenKind::Lifetime,
+                    _ => TokenKind::Ident,
+                };
+                tokens.push(Token {
+                    kind,
+                    text: &input[offset..end],
+                    offset,
+                });
+                continue;
+            }
+            ':' => match chars.next() {
+                Some((_, ':')) => TokenKind::PathSep,
+                _ => return Err(error(input, offset, "expected `::`")),
+            },
+            '<' => TokenKind::Lt,
+            '>' => TokenKind::Gt,
+            '(' => TokenKind::OpenParen,
+            ')' => TokenKind::CloseParen,
+            ',' => TokenKind::Comma,
+            '[' => TokenKind::OpenBracket,
+            ']' => TokenKind::CloseBracket,
+            ';' => TokenKind::Semi,
+            _ => return Err(error(input, offset, &format!("unexpected `{}`", c))),
+        };
+        let len = if kind == TokenKind::PathSep { 2 } else { 1 };
+        tokens.push(Token {
+            kind,
+            text: &input[offset..offset + len],
+            offset,
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// Error showing the input with a marker below the offending character.
+fn error(input: &str, offset: usize, msg: &str) -> color_eyre::Report {
+    let column = input[..offset].chars().count();
+    eyre::eyre!(
+        "{} at column {} of type `{}`\n  {}\n  {}^",
+        msg,
+        column + 1,
+        input,
+        input,
+        " ".repeat(column)
+    )
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: &'a [Token<'a>],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn peek_kind(&self) -> Option<TokenKind> {
+        self.peek().map(|token| token.kind)
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn error_at(&self, token: Token, msg: &str) -> color_eyre::Report {
+        error(self.input, token.offset, msg)
+    }
+
+    fn error_at_end(&self, msg: &str) -> color_eyre::Report {
+        error(self.input, self.input.len(), msg)
+    }
+
+    fn expect(&mut self, kind: TokenKind, msg: &str) -> color_eyre::Result<Token<'a>> {
+        match self.next() {
+            Some(token) if token.kind == kind => Ok(token),
+            Some(token) => Err(self.error_at(token, msg)),
+            None => Err(self.error_at_end(msg)),
+        }
+    }
+
+    fn parse_type(&mut self) -> color_eyre::Result<Type> {
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::OpenParen,
+                ..
+            }) => self.parse_tuple(),
+            Some(Token {
+                kind: TokenKind::OpenBracket,
+                ..
+            }) => self.parse_array(),
+            Some(Token {
+                kind: TokenKind::Lt,
+                ..
+            }) => self.parse_qualified(),
+            Some(Token {
+                kind: TokenKind::Ident | TokenKind::PathSep,
+                ..
+            }) => Ok(Type::Path(self.parse_path()?)),
+            Some(token) => Err(self.error_at(token, "expected a type")),
+            None => Err(self.error_at_end("expected a type")),
+        }
+    }
+
+    /// `()`, `(A,)` and `(A, B)`, while `(A)` is just `A`.
+    fn parse_tuple(&mut self) -> color_eyre::Result<Type> {
+        self.expect(TokenKind::OpenParen, "expected `(`")?;
+        let mut fields = Vec::new();
+        let mut trailing_comma = false;
+
+        while self.peek_kind() != Some(TokenKind::CloseParen) {
+            fields.push(self.parse_type()?);
+            trailing_comma = self.peek_kind() == Some(TokenKind::Comma);
+            if trailing_comma {
+                self.next();
+            } else if self.peek_kind() != Some(TokenKind::CloseParen) {
+                return Err(self.unexpected("expected `,` or `)`"));
+            }
+        }
+        self.expect(TokenKind::CloseParen, "expected `)`")?;
+
+        if fields.len() == 1 && !trailing_comma {
+            Ok(fields.remove(0))
+        } else {
+            Ok(Type::Tuple(fields))
+        }
+    }
+
+    /// `[T; N]` and `[T]`
+    fn parse_array(&mut self) -> color_eyre::Result<Type> {
+        self.expect(TokenKind::OpenBracket, "expected `[`")?;
+        let ty = self.parse_type()?;
+        if self.peek_kind() == Some(TokenKind::CloseBracket) {
+            self.next();
+            return Ok(Type::Slice(Box::new(ty)));
+        }
+        self.expect(TokenKind::Semi, "expected `;` or `]`")?;
+        let len = self.expect(TokenKind::Ident, "expected the array length")?;
+        let len = len
+            .text
+            .parse()
+            .map_err(|_| self.error_at(len, "expected the array length"))?;
+        self.expect(TokenKind::CloseBracket, "expected `]`")?;
+        Ok(Type::Array(Box::new(ty), len))
+    }
+
+    /// `<T as Trait>::Item`
+    fn parse_qualified(&mut self) -> color_eyre::Result<Type> {
+        self.expect(TokenKind::Lt, "expected `<`")?;
+        let ty = self.parse_type()?;
+        match self.next() {
+            Some(token) if token.kind == TokenKind::Ident && token.text == "as" => {}
+            Some(token) => return Err(self.error_at(token, "expected `as`")),
+            None => return Err(self.error_at_end("expected `as`")),
+        }
+        let trait_ = self.parse_path()?;
+        self.expect(TokenKind::Gt, "expected `>`")?;
+
+        let mut item = Vec::new();
+        while self.peek_kind() == Some(TokenKind::PathSep) {
+            self.next();
+            item.push(self.parse_segment()?);
+        }
+        if item.is_empty() {
+            return Err(self.unexpected("expected `::` after qualified type"));
+        }
+
+        Ok(Type::Qualified {
+            ty: Box::new(ty),
+            trait_,
+            item,
+        })
+    }
+
+    /// `a::b::C<D, E>` with an optional leading `::`.
+    fn parse_path(&mut self) -> color_eyre::Result<Path> {
+        if self.peek_kind() == Some(TokenKind::PathSep) {
+            self.next();
+        }
+        let mut segments = vec![self.parse_segment()?];
+        while self.peek_kind() == Some(TokenKind::PathSep) {
+            self.next();
+            segments.push(self.parse_segment()?);
+        }
+        Ok(Path { segments })
+    }
+
+    fn parse_segment(&mut self) -> color_eyre::Result<Segment> {
+        let ident = self.expect(TokenKind::Ident, "expected an identifier")?;
+        let mut args = Vec::new();
+
+        if self.peek_kind() == Some(TokenKind::Lt) {
+            self.next();
+            while self.peek_kind() != Some(TokenKind::Gt) {
+                // lifetimes have no meaning for the generated types
+                if self.peek_kind() == Some(TokenKind::Lifetime) {
+                    self.next();
+                } else {
+                    args.push(self.parse_type()?);
+                }
+                if self.peek_kind() == Some(TokenKind::Comma) {
+                    self.next();
+                } else if self.peek_kind() != Some(TokenKind::Gt) {
+                    return Err(self.unexpected("expected `,` or `>`"));
+                }
+            }
+            self.expect(TokenKind::Gt, "expected `>`")?;
+        }
+
+        Ok(Segment {
+            ident: ident.text.to_string(),
+            args,
+        })
+    }
+
+    fn unexpected(&self, msg: &str) -> color_eyre::Report {
+        match self.peek() {
+            Some(token) => self.error_at(token, msg),
+            None => self.error_at_end(msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> RuntimeType {
+        parse_type(input, &Config::default()).unwrap()
+    }
+
+    fn parse_err(input: &str) -> String {
+        parse_type(input, &Config::default())
+            .unwrap_err()
+            .to_string()
+    }
+
+    fn primitive(ty: &str) -> RuntimeType {
+        RuntimeType::Primitive(ty.to_string())
+    }
+
+    fn tuple(fields: Vec<RuntimeType>) -> RuntimeType {
+        RuntimeType::Tuple(Box::new(fields))
+    }
+
+    #[test]
+    fn nested_tuples_and_vecs() {
+        assert_eq!(
+            parse("Vec<(T::AccountId, Vec<u8>)>"),
+            RuntimeType::Vec(Box::new(tuple(vec![
+                RuntimeType::System("AccountId".to_string()),
+                RuntimeType::Vec(Box::new(primitive("u8"))),
+            ])))
+        );
+        assert_eq!(
+            parse("(Option<u32>, Vec<(u8, u8)>)"),
+            tuple(vec![
+                RuntimeType::Option(Box::new(primitive("u32"))),
+                RuntimeType::Vec(Box::new(tuple(vec![primitive("u8"), primitive("u8")]))),
+            ])
+        );
+    }
+
+    #[test]
+    fn parenthesized_type_is_not_a_tuple() {
+        assert_eq!(parse("(u32)"), primitive("u32"));
+        assert_eq!(parse("(u32,)"), tuple(vec![primitive("u32")]));
+        assert_eq!(parse("()"), tuple(vec![]));
+    }
+
+    #[test]
+    fn generic_params_are_dropped() {
+        let balance = RuntimeType::Complex("BalanceOf".to_string());
+        assert_eq!(parse("BalanceOf<T>"), balance);
+        assert_eq!(parse("BalanceOf<T, I>"), balance);
+        assert_eq!(
+            parse("BalanceLock<T::Balance>"),
+            RuntimeType::Generic(
+                Box::new(RuntimeType::Complex("BalanceLock".to_string())),
+                vec![RuntimeType::Complex("Balance".to_string())],
+            )
+        );
+        let param = Box::new(RuntimeType::Complex("T".to_string()));
+        assert_eq!(parse("Vec<T>"), RuntimeType::Vec(param.clone()));
+        assert_eq!(parse("Option<T>"), RuntimeType::Option(param));
+    }
+
+    #[test]
+    fn lifetimes_are_skipped() {
+        assert_eq!(
+            parse("Cow<'static, [u8]>"),
+            RuntimeType::Vec(Box::new(primitive("u8")))
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        assert_eq!(
+            parse("[u8; 32]"),
+            RuntimeType::Array(Box::new(primitive("u8")), 32)
+        );
+    }
+
+    #[test]
+    fn qualified_paths() {
+        assert_eq!(
+            parse("<T::Lookup as StaticLookup>::Source"),
+            RuntimeType::System("Address".to_string())
+        );
+        assert_eq!(
+            parse("<T as Trait>::Balance"),
+            RuntimeType::Complex("Balance".to_string())
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_column() {
+        assert_eq!(
+            parse_err("Vec<u8"),
+            "expected `,` or `>` at column 7 of type `Vec<u8`\n  Vec<u8\n        ^"
+        );
+        assert_eq!(
+            parse_err("u32)"),
+            "expected end of type at column 4 of type `u32)`\n  u32)\n     ^"
+        );
+        assert_eq!(
+            parse_err("[u8; x]"),
+            "expected the array length at column 6 of type `[u8; x]`\n  [u8; x]\n       ^"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use crate::decode_json_response;
+use color_eyre::eyre::{self, WrapErr};
+use frame_metadata::RuntimeMetadataPrefixed;
+use tungstenite::Message;
+
+/// Fetch the metadata of a node over HTTP or WebSocket, optionally at
+/// the given block hash.
+pub fn fetch_metadata(
+    url: &str,
+    at: Option<String>,
+) -> color_eyre::Result<RuntimeMetadataPrefixed> {
+    let params = at.map(|hash| vec![hash]).unwrap_or_default();
+    let json = rpc_request(url, "state_getMetadata", ureq::json!(params))
+        .context("error fetching metadata from the substrate node")?;
+
+    decode_json_response(&json)
+}
+
+/// Fetch the hash of the block with the given number.
+pub fn fetch_block_hash(url: &str, number: u64) -> color_eyre::Result<String> {
+    let json = rpc_request(url, "chain_getBlockHash", ureq::json!([number]))
+        .context("error fetching block hash from the substrate node")?;
+
+    let hash = json["result"]
+        .as_str()
+        .ok_or(eyre::eyre!("block {} does not exist", number))?;
+
+    Ok(hash.to_string())
+}
+
+/// Send a JSON-RPC request, the transport is picked from the URL scheme.
+pub fn rpc_request(
+    url: &str,
+    method: &str,
+    params: serde_json::Value,
+) -> color_eyre::Result<serde_json::Value> {
+    let request = ureq::json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+        "id": 1
+    });
+
+    let json = if url.starts_with("ws://") || url.starts_with("wss://") {
+        ws_request(url, &request)?
+    } else {
+        http_request(url, &request)?
+    };
+
+    if let Some(error) = json.get("error") {
+        return Err(eyre::eyre!("{} failed: {}", method, error));
+    }
+
+    Ok(json)
+}
+
+fn http_request(url: &str, request: &serde_json::Value) -> color_eyre::Result<serde_json::Value> {
+    let resp = ureq::post(url)
+        .set("Content-Type", "application/json")
+        .send_json(request.clone())?;
+
+    Ok(resp.into_json()?)
+}
+
+fn ws_request(url: &str, request: &serde_json::Value) -> color_eyre::Result<serde_json::Value> {
+    let (mut socket, _) = tungstenite::connect(url)?;
+    socket.send(Message::Text(request.to_string()))?;
+
+    let json = loop {
+        match socket.read()? {
+            Message::Text(text) => break serde_json::from_str(&text)?,
+            Message::Binary(bytes) => break serde_json::from_slice(&bytes)?,
+            Message::Close(_) => return Err(eyre::eyre!("connection closed before response")),
+            _ => continue,
+        }
+    };
+
+    // the response has been received so failing to close is not an error
+    let _ = socket.close(None);
+
+    Ok(json)
+}
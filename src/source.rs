@@ -0,0 +1,41 @@
+use color_eyre::eyre::{self, WrapErr};
+use frame_metadata::{RuntimeMetadataPrefixed, META_RESERVED};
+
+/// Decode metadata stored as raw SCALE bytes, a hex string or a JSON-RPC response.
+pub fn parse_metadata(bytes: &[u8]) -> color_eyre::Result<RuntimeMetadataPrefixed> {
+    if bytes.starts_with(&META_RESERVED.to_le_bytes()) {
+        return decode_bytes(bytes);
+    }
+
+    let text = std::str::from_utf8(bytes)
+        .context("metadata file should be SCALE encoded, hex or JSON")?
+        .trim();
+
+    if text.starts_with('{') {
+        let json: serde_json::Value = serde_json::from_str(text)?;
+        decode_json_response(&json)
+    } else {
+        decode_hex(text)
+    }
+}
+
+/// Decode the hex encoded metadata in the result of a JSON-RPC response.
+pub fn decode_json_response(
+    json: &serde_json::Value,
+) -> color_eyre::Result<RuntimeMetadataPrefixed> {
+    let hex_data = json["result"]
+        .as_str()
+        .ok_or(eyre::eyre!("metadata result field should be a string"))?;
+
+    decode_hex(hex_data)
+}
+
+fn decode_hex(hex_data: &str) -> color_eyre::Result<RuntimeMetadataPrefixed> {
+    let bytes = hex::decode(hex_data.trim_start_matches("0x"))?;
+    decode_bytes(&bytes)
+}
+
+fn decode_bytes(bytes: &[u8]) -> color_eyre::Result<RuntimeMetadataPrefixed> {
+    let decoded = scale::Decode::decode(&mut &bytes[..])?;
+    Ok(decoded)
+}
@@ -0,0 +1,38 @@
+use crate::module::StorageHasher;
+
+decode_module!(
+    v13,
+    v13::StorageEntryType::NMap {
+        keys,
+        hashers,
+        value,
+    } => |config| {
+        let keys = keys.decode_or_else()?;
+        let hashers = hashers.decode_or_else()?;
+        if keys.len() != hashers.len() {
+            return Err(color_eyre::eyre::eyre!(
+                "Storage n-map should have a key for each hasher"
+            ));
+        }
+        StorageEntryType::NMap {
+            keys: keys
+                .iter()
+                .map(|key| RuntimeType::parse(key, config))
+                .collect::<color_eyre::Result<_>>()?,
+            hashers: hashers.into_iter().map(decode_hasher).collect(),
+            value: RuntimeType::parse(&value.decode_or_else()?, config)?,
+        }
+    },
+);
+
+fn decode_hasher(hasher: v13::StorageHasher) -> StorageHasher {
+    match hasher {
+        v13::StorageHasher::Blake2_128 => StorageHasher::Blake2_128,
+        v13::StorageHasher::Blake2_256 => StorageHasher::Blake2_256,
+        v13::StorageHasher::Blake2_128Concat => StorageHasher::Blake2_128Concat,
+        v13::StorageHasher::Twox128 => StorageHasher::Twox128,
+        v13::StorageHasher::Twox256 => StorageHasher::Twox256,
+        v13::StorageHasher::Twox64Concat => StorageHasher::Twox64Concat,
+        v13::StorageHasher::Identity => StorageHasher::Identity,
+    }
+}
@@ -0,0 +1,313 @@
+use crate::config::ItemKind;
+use crate::module::{
+    self, ConstantMetadata, ErrorMetadata, EventMetadata, FunctionArgumentMetadata,
+    FunctionMetadata, ModuleMetadata, StorageEntryMetadata, StorageEntryType, StorageHasher,
+};
+use crate::Config;
+use color_eyre::eyre::{self, WrapErr};
+use frame_metadata::v14;
+use scale_info::{form::PortableForm, TypeDef, Variant};
+
+mod types;
+
+pub use types::{TypeGenerator, TYPES_MODULE};
+
+/// Generated pallet modules are siblings of the types module.
+const ROOT: &str = "super::";
+
+pub fn add_module(
+    pallet: v14::PalletMetadata<PortableForm>,
+    types: &TypeGenerator,
+    config: &Config,
+    modules: &mut module::Modules,
+) -> color_eyre::Result<()> {
+    let name = pallet.name;
+
+    if !config.includes_module(&name)? {
+        return Ok(());
+    }
+
+    let calls = if let Some(calls) = pallet.calls {
+        let variants = variants(types, calls.ty.id)
+            .with_context(|| format!("error decoding the calls of pallet `{}`", name));
+        let variants = config.recover(variants)?.unwrap_or_default();
+        select(
+            variants,
+            |variant| &variant.name,
+            &name,
+            ItemKind::Call,
+            config,
+        )?
+        .into_iter()
+        .map(|variant| {
+            decode_call(types, variant).with_context(|| {
+                format!(
+                    "error decoding call `{}` of pallet `{}`",
+                    variant.name, name
+                )
+            })
+        })
+        .filter_map(|call| config.recover(call).transpose())
+        .collect::<color_eyre::Result<_>>()?
+    } else {
+        Vec::new()
+    };
+
+    let events = if let Some(event) = pallet.event {
+        let variants = variants(types, event.ty.id)
+            .with_context(|| format!("error decoding the events of pallet `{}`", name));
+        let variants = config.recover(variants)?.unwrap_or_default();
+        select(
+            variants,
+            |variant| &variant.name,
+            &name,
+            ItemKind::Event,
+            config,
+        )?
+        .into_iter()
+        .map(|variant| {
+            decode_event(types, variant).with_context(|| {
+                format!(
+                    "error decoding event `{}` of pallet `{}`",
+                    variant.name, name
+                )
+            })
+        })
+        .filter_map(|event| config.recover(event).transpose())
+        .collect::<color_eyre::Result<_>>()?
+    } else {
+        Vec::new()
+    };
+
+    let storage = if let Some(storage) = pallet.storage {
+        let entries = storage.entries;
+        select(
+            entries,
+            |entry| &entry.name,
+            &name,
+            ItemKind::Storage,
+            config,
+        )?
+        .into_iter()
+        .map(|entry| {
+            let entry_name = entry.name.clone();
+            decode_storage_entry(types, entry).with_context(|| {
+                format!(
+                    "error decoding storage `{}` of pallet `{}`",
+                    entry_name, name
+                )
+            })
+        })
+        .filter_map(|entry| config.recover(entry).transpose())
+        .collect::<color_eyre::Result<_>>()?
+    } else {
+        Vec::new()
+    };
+
+    let constants = select(
+        pallet.constants,
+        |constant| &constant.name,
+        &name,
+        ItemKind::Constant,
+        config,
+    )?
+    .into_iter()
+    .map(|constant| {
+        let constant_name = constant.name.clone();
+        decode_constant(types, constant).with_context(|| {
+            format!(
+                "error decoding constant `{}` of pallet `{}`",
+                constant_name, name
+            )
+        })
+    })
+    .filter_map(|constant| config.recover(constant).transpose())
+    .collect::<color_eyre::Result<_>>()?;
+
+    let errors = if let Some(error) = pallet.error {
+        let variants = variants(types, error.ty.id)
+            .with_context(|| format!("error decoding the errors of pallet `{}`", name));
+        let variants = config.recover(variants)?.unwrap_or_default();
+        select(
+            variants,
+            |variant| &variant.name,
+            &name,
+            ItemKind::Error,
+            config,
+        )?
+        .into_iter()
+        .map(|variant| {
+            decode_error(variant).with_context(|| {
+                format!(
+                    "error decoding error `{}` of pallet `{}`",
+                    variant.name, name
+                )
+            })
+        })
+        .filter_map(|error| config.recover(error).transpose())
+        .collect::<color_eyre::Result<_>>()?
+    } else {
+        Vec::new()
+    };
+
+    module::add_module(
+        ModuleMetadata {
+            name,
+            index: pallet.index,
+            calls,
+            events,
+            storage,
+            constants,
+            errors,
+        },
+        config,
+        modules,
+    )
+}
+
+/// The items selected by the config, checked before their types are
+/// resolved so that skipped items do not pull in registry types.
+fn select<T>(
+    items: impl IntoIterator<Item = T>,
+    item_name: impl Fn(&T) -> &str,
+    module_name: &str,
+    kind: ItemKind,
+    config: &Config,
+) -> color_eyre::Result<Vec<T>> {
+    let mut selected = Vec::new();
+    for item in items {
+        if config.includes_item(module_name, kind, item_name(&item))? {
+            selected.push(item);
+        }
+    }
+    Ok(selected)
+}
+
+fn variants<'a>(
+    types: &TypeGenerator<'a>,
+    id: u32,
+) -> color_eyre::Result<&'a [Variant<PortableForm>]> {
+    match &types.lookup(id)?.type_def {
+        TypeDef::Variant(variant) => Ok(&variant.variants),
+        _ => Err(eyre::eyre!("Type {} should be a variant", id)),
+    }
+}
+
+fn decode_call(
+    types: &TypeGenerator,
+    variant: &Variant<PortableForm>,
+) -> color_eyre::Result<FunctionMetadata> {
+    Ok(FunctionMetadata {
+        name: variant.name.clone(),
+        arguments: variant
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                Ok(FunctionArgumentMetadata {
+                    name: field.name.clone().unwrap_or_else(|| format!("arg{}", i)),
+                    ty: types.resolve(field.ty.id, ROOT)?,
+                })
+            })
+            .collect::<color_eyre::Result<_>>()?,
+        docs: variant.docs.clone(),
+    })
+}
+
+fn decode_event(
+    types: &TypeGenerator,
+    variant: &Variant<PortableForm>,
+) -> color_eyre::Result<EventMetadata> {
+    Ok(EventMetadata {
+        name: variant.name.clone(),
+        arguments: variant
+            .fields
+            .iter()
+            .map(|field| types.resolve(field.ty.id, ROOT))
+            .collect::<color_eyre::Result<_>>()?,
+        docs: variant.docs.clone(),
+    })
+}
+
+fn decode_constant(
+    types: &TypeGenerator,
+    constant: v14::PalletConstantMetadata<PortableForm>,
+) -> color_eyre::Result<ConstantMetadata> {
+    Ok(ConstantMetadata {
+        ty: types.resolve(constant.ty.id, ROOT)?,
+        name: constant.name,
+        value: constant.value,
+        docs: constant.docs,
+    })
+}
+
+fn decode_error(variant: &Variant<PortableForm>) -> color_eyre::Result<ErrorMetadata> {
+    if !variant.fields.is_empty() {
+        return Err(eyre::eyre!("Errors with fields are not supported"));
+    }
+
+    Ok(ErrorMetadata {
+        name: variant.name.clone(),
+        index: variant.index,
+        docs: variant.docs.clone(),
+    })
+}
+
+fn decode_hasher(hasher: v14::StorageHasher) -> StorageHasher {
+    match hasher {
+        v14::StorageHasher::Blake2_128 => StorageHasher::Blake2_128,
+        v14::StorageHasher::Blake2_256 => StorageHasher::Blake2_256,
+        v14::StorageHasher::Blake2_128Concat => StorageHasher::Blake2_128Concat,
+        v14::StorageHasher::Twox128 => StorageHasher::Twox128,
+        v14::StorageHasher::Twox256 => StorageHasher::Twox256,
+        v14::StorageHasher::Twox64Concat => StorageHasher::Twox64Concat,
+        v14::StorageHasher::Identity => StorageHasher::Identity,
+    }
+}
+
+fn decode_storage_entry(
+    types: &TypeGenerator,
+    entry: v14::StorageEntryMetadata<PortableForm>,
+) -> color_eyre::Result<StorageEntryMetadata> {
+    let ty = match entry.ty {
+        v14::StorageEntryType::Plain(plain) => {
+            StorageEntryType::Plain(types.resolve(plain.id, ROOT)?)
+        }
+        // the hashers are known up front so every map is keyed like
+        // an n-map, multiple keys are given as a tuple in the registry
+        v14::StorageEntryType::Map {
+            hashers,
+            key,
+            value,
+        } => {
+            let keys = if hashers.len() == 1 {
+                vec![types.resolve(key.id, ROOT)?]
+            } else {
+                match &types.lookup(key.id)?.type_def {
+                    TypeDef::Tuple(tuple) if tuple.fields.len() == hashers.len() => tuple
+                        .fields
+                        .iter()
+                        .map(|field| types.resolve(field.id, ROOT))
+                        .collect::<color_eyre::Result<_>>()?,
+                    _ => {
+                        return Err(eyre::eyre!(
+                            "Storage {} should have a key for each hasher",
+                            entry.name
+                        ))
+                    }
+                }
+            };
+            StorageEntryType::NMap {
+                keys,
+                hashers: hashers.into_iter().map(decode_hasher).collect(),
+                value: types.resolve(value.id, ROOT)?,
+            }
+        }
+    };
+
+    Ok(StorageEntryMetadata {
+        name: entry.name,
+        ty,
+        docs: entry.docs,
+    })
+}
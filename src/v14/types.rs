@@ -0,0 +1,427 @@
+use crate::module::{ident, parse_type};
+use crate::{Config, RuntimeType};
+use color_eyre::eyre::{self, WrapErr};
+use heck::SnakeCase;
+use proc_macro2::TokenStream;
+use quote::{quote, TokenStreamExt};
+use scale_info::{
+    form::PortableForm, Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive, Variant,
+};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Name of the module that holds every generated registry type.
+pub const TYPES_MODULE: &str = "runtime_types";
+
+/// Resolves registry types to Rust types, generating the definition of
+/// every type that needs one so they can be emitted afterwards.
+pub struct TypeGenerator<'a> {
+    registry: &'a PortableRegistry,
+    config: &'a Config,
+    names: HashMap<u32, (Vec<String>, String)>,
+    /// Types whose definition is generated or being generated.
+    used: RefCell<BTreeSet<u32>>,
+    definitions: RefCell<BTreeMap<u32, TokenStream>>,
+}
+
+#[derive(Default)]
+struct TypesModule {
+    types: TokenStream,
+    children: BTreeMap<String, TypesModule>,
+}
+
+impl TypesModule {
+    fn insert(&mut self, namespace: &[String], ty: TokenStream) {
+        match namespace.split_first() {
+            Some((head, tail)) => self
+                .children
+                .entry(head.to_snake_case())
+                .or_default()
+                .insert(tail, ty),
+            None => self.types.append_all(ty),
+        }
+    }
+
+    fn into_token_stream(self, config: &Config) -> TokenStream {
+        let types = self.types;
+        let children = self.children.into_iter().map(|(name, child)| {
+            let ident = ident(&name, config);
+            let child = child.into_token_stream(config);
+            quote!(pub mod #ident { #child })
+        });
+        quote! {
+            use parity_scale_codec::{Encode, Decode};
+
+            #types
+            #(#children)*
+        }
+    }
+}
+
+fn is_builtin(ty: &Type<PortableForm>) -> bool {
+    matches!(
+        ty.path.segments.as_slice(),
+        [name] if ["Option", "Result", "Cow", "BTreeMap", "BTreeSet"].contains(&name.as_str())
+    )
+}
+
+impl<'a> TypeGenerator<'a> {
+    pub fn new(registry: &'a PortableRegistry, config: &'a Config) -> Self {
+        let mut names = HashMap::new();
+        let mut seen = HashMap::<Vec<String>, usize>::new();
+
+        for ty in registry.types.iter() {
+            if is_builtin(&ty.ty) || ty.ty.path.segments.is_empty() {
+                continue;
+            }
+            if !matches!(ty.ty.type_def, TypeDef::Composite(_) | TypeDef::Variant(_)) {
+                continue;
+            }
+
+            // generic types appear once per instantiation so later
+            // instantiations of the same path are suffixed by a counter
+            let segments = ty.ty.path.segments.clone();
+            let count = seen.entry(segments.clone()).or_insert(0);
+            *count += 1;
+
+            let (name, namespace) = segments.split_last().expect("path is not empty");
+            let name = if *count == 1 {
+                name.clone()
+            } else {
+                format!("{}{}", name, count)
+            };
+            names.insert(ty.id, (namespace.to_vec(), name));
+        }
+
+        Self {
+            registry,
+            config,
+            names,
+            used: RefCell::new(BTreeSet::new()),
+            definitions: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn lookup(&self, id: u32) -> color_eyre::Result<&'a Type<PortableForm>> {
+        self.registry
+            .resolve(id)
+            .ok_or_else(|| eyre::eyre!("Type {} not found in registry", id))
+    }
+
+    fn type_param(
+        &self,
+        ty: &Type<PortableForm>,
+        index: usize,
+        root: &str,
+    ) -> color_eyre::Result<RuntimeType> {
+        let param = ty
+            .type_params
+            .get(index)
+            .and_then(|param| param.ty.as_ref())
+            .ok_or_else(|| {
+                eyre::eyre!("Type {:?} is missing parameter {}", ty.path.segments, index)
+            })?;
+        self.resolve(param.id, root)
+    }
+
+    /// Resolve the type with the given `id`, generated types are
+    /// referenced through `root` which must point at the parent
+    /// of the types module.
+    ///
+    /// Types that need a definition are generated right away so that a
+    /// type which cannot be generated fails the items using it.
+    pub fn resolve(&self, id: u32, root: &str) -> color_eyre::Result<RuntimeType> {
+        let ty = self.lookup(id)?;
+
+        if let Some((namespace, name)) = self.names.get(&id) {
+            // recursive types are already being generated further up
+            if !self.used.borrow().contains(&id) {
+                self.generate_definition(id)?;
+            }
+            let path = std::iter::once(TYPES_MODULE.to_string())
+                .chain(
+                    namespace
+                        .iter()
+                        .map(|segment| ident(&segment.to_snake_case(), self.config).to_string()),
+                )
+                .chain(std::iter::once(ident(name, self.config).to_string()))
+                .collect::<Vec<_>>()
+                .join("::");
+            return Ok(RuntimeType::Path(format!("{}{}", root, path)));
+        }
+
+        let runtime_type = match &ty.type_def {
+            TypeDef::Composite(_) | TypeDef::Variant(_) => {
+                match ty.path.segments.last().map(String::as_str) {
+                    Some("Option") => RuntimeType::Option(Box::new(self.type_param(ty, 0, root)?)),
+                    Some("Result") => RuntimeType::Generic(
+                        Box::new(RuntimeType::Path("Result".to_string())),
+                        vec![self.type_param(ty, 0, root)?, self.type_param(ty, 1, root)?],
+                    ),
+                    Some("Cow") => self.type_param(ty, 0, root)?,
+                    Some("BTreeMap") => RuntimeType::Generic(
+                        Box::new(RuntimeType::Path("std::collections::BTreeMap".to_string())),
+                        vec![self.type_param(ty, 0, root)?, self.type_param(ty, 1, root)?],
+                    ),
+                    Some("BTreeSet") => RuntimeType::Generic(
+                        Box::new(RuntimeType::Path("std::collections::BTreeSet".to_string())),
+                        vec![self.type_param(ty, 0, root)?],
+                    ),
+                    _ => return Err(eyre::eyre!("Type {} has no path", id)),
+                }
+            }
+            TypeDef::Sequence(seq) => {
+                RuntimeType::Vec(Box::new(self.resolve(seq.type_param.id, root)?))
+            }
+            TypeDef::Array(arr) => RuntimeType::Array(
+                Box::new(self.resolve(arr.type_param.id, root)?),
+                arr.len as usize,
+            ),
+            TypeDef::Tuple(tuple) => RuntimeType::Tuple(Box::new(
+                tuple
+                    .fields
+                    .iter()
+                    .map(|field| self.resolve(field.id, root))
+                    .collect::<color_eyre::Result<_>>()?,
+            )),
+            TypeDef::Primitive(primitive) => match primitive {
+                TypeDefPrimitive::Bool => RuntimeType::Primitive("bool".to_string()),
+                TypeDefPrimitive::Char => RuntimeType::Primitive("char".to_string()),
+                TypeDefPrimitive::Str => RuntimeType::Path("String".to_string()),
+                TypeDefPrimitive::U8 => RuntimeType::Primitive("u8".to_string()),
+                TypeDefPrimitive::U16 => RuntimeType::Primitive("u16".to_string()),
+                TypeDefPrimitive::U32 => RuntimeType::Primitive("u32".to_string()),
+                TypeDefPrimitive::U64 => RuntimeType::Primitive("u64".to_string()),
+                TypeDefPrimitive::U128 => RuntimeType::Primitive("u128".to_string()),
+                TypeDefPrimitive::U256 => RuntimeType::Path("sp_core::U256".to_string()),
+                TypeDefPrimitive::I8 => RuntimeType::Primitive("i8".to_string()),
+                TypeDefPrimitive::I16 => RuntimeType::Primitive("i16".to_string()),
+                TypeDefPrimitive::I32 => RuntimeType::Primitive("i32".to_string()),
+                TypeDefPrimitive::I64 => RuntimeType::Primitive("i64".to_string()),
+                TypeDefPrimitive::I128 => RuntimeType::Primitive("i128".to_string()),
+                TypeDefPrimitive::I256 => {
+                    RuntimeType::Array(Box::new(RuntimeType::Primitive("u8".to_string())), 32)
+                }
+            },
+            TypeDef::Compact(compact) => {
+                RuntimeType::Compact(Box::new(self.compact_inner(compact.type_param.id, root)?))
+            }
+            TypeDef::BitSequence(bits) => {
+                let order = self
+                    .lookup(bits.bit_order_type.id)?
+                    .path
+                    .segments
+                    .last()
+                    .cloned()
+                    .unwrap_or_else(|| "Lsb0".to_string());
+                RuntimeType::Path(format!(
+                    "bitvec::vec::BitVec<{}, bitvec::order::{}>",
+                    self.resolve(bits.bit_store_type.id, root)?,
+                    order
+                ))
+            }
+        };
+
+        Ok(runtime_type)
+    }
+
+    /// Compact encoding is only defined for primitives, wrapper types
+    /// such as `Perbill` are encoded as the primitive they contain.
+    fn compact_inner(&self, id: u32, root: &str) -> color_eyre::Result<RuntimeType> {
+        match &self.lookup(id)?.type_def {
+            TypeDef::Composite(composite) if composite.fields.len() == 1 => {
+                self.compact_inner(composite.fields[0].ty.id, root)
+            }
+            TypeDef::Primitive(_) => self.resolve(id, root),
+            _ => Err(eyre::eyre!("Type {} cannot be compact encoded", id)),
+        }
+    }
+
+    /// Whether `id` embeds `target` without any heap indirection,
+    /// in which case the field must be boxed.
+    fn contains(&self, id: u32, target: u32, visited: &mut BTreeSet<u32>) -> bool {
+        if id == target {
+            return true;
+        }
+        if !visited.insert(id) {
+            return false;
+        }
+        let ty = match self.lookup(id) {
+            Ok(ty) => ty,
+            Err(_) => return false,
+        };
+        match &ty.type_def {
+            TypeDef::Composite(composite) => composite
+                .fields
+                .iter()
+                .any(|field| self.contains(field.ty.id, target, visited)),
+            TypeDef::Variant(variant) => variant
+                .variants
+                .iter()
+                .flat_map(|variant| variant.fields.iter())
+                .any(|field| self.contains(field.ty.id, target, visited)),
+            TypeDef::Array(arr) => self.contains(arr.type_param.id, target, visited),
+            TypeDef::Tuple(tuple) => tuple
+                .fields
+                .iter()
+                .any(|field| self.contains(field.id, target, visited)),
+            TypeDef::Compact(compact) => self.contains(compact.type_param.id, target, visited),
+            TypeDef::Sequence(_) | TypeDef::Primitive(_) | TypeDef::BitSequence(_) => false,
+        }
+    }
+
+    fn field(
+        &self,
+        owner: u32,
+        index: usize,
+        field: &Field<PortableForm>,
+        root: &str,
+        visibility: &TokenStream,
+    ) -> color_eyre::Result<TokenStream> {
+        let context = || match &field.name {
+            Some(name) => format!("error generating field `{}`", name),
+            None => format!("error generating field {}", index),
+        };
+        let (compact, ty) = match &self.lookup(field.ty.id).with_context(context)?.type_def {
+            TypeDef::Compact(compact) => (
+                quote!(#[codec(compact)]),
+                self.compact_inner(compact.type_param.id, root)
+                    .with_context(context)?,
+            ),
+            _ => (
+                quote!(),
+                self.resolve(field.ty.id, root).with_context(context)?,
+            ),
+        };
+        let ty = parse_type(&ty).with_context(context)?;
+        let ty = if self.contains(field.ty.id, owner, &mut BTreeSet::new()) {
+            quote!(Box<#ty>)
+        } else {
+            quote!(#ty)
+        };
+        match &field.name {
+            Some(name) => {
+                let name = ident(name, self.config);
+                Ok(quote!(#compact #visibility #name: #ty))
+            }
+            None => Ok(quote!(#compact #visibility #ty)),
+        }
+    }
+
+    fn fields(
+        &self,
+        owner: u32,
+        fields: &[Field<PortableForm>],
+        root: &str,
+        visibility: TokenStream,
+    ) -> color_eyre::Result<TokenStream> {
+        if fields.is_empty() {
+            return Ok(quote!());
+        }
+
+        let fields_stream = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| self.field(owner, index, field, root, &visibility))
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+
+        if fields.iter().all(|field| field.name.is_some()) {
+            Ok(quote!({ #(#fields_stream,)* }))
+        } else {
+            Ok(quote!(( #(#fields_stream,)* )))
+        }
+    }
+
+    fn variant(
+        &self,
+        owner: u32,
+        variant: &Variant<PortableForm>,
+        root: &str,
+    ) -> color_eyre::Result<TokenStream> {
+        let name = ident(&variant.name, self.config);
+        let index = proc_macro2::Literal::u8_unsuffixed(variant.index);
+        let fields = self
+            .fields(owner, &variant.fields, root, quote!())
+            .with_context(|| format!("error generating variant `{}`", variant.name))?;
+        Ok(quote! {
+            #[codec(index = #index)]
+            #name #fields,
+        })
+    }
+
+    /// Generate the definition of `id`, leaving out every type it pulled
+    /// in if it cannot be generated.
+    fn generate_definition(&self, id: u32) -> color_eyre::Result<()> {
+        let used = self.used.borrow().clone();
+        self.used.borrow_mut().insert(id);
+        match self.definition(id) {
+            Ok(definition) => {
+                self.definitions.borrow_mut().insert(id, definition);
+                Ok(())
+            }
+            Err(err) => {
+                self.definitions
+                    .borrow_mut()
+                    .retain(|id, _| used.contains(id));
+                *self.used.borrow_mut() = used;
+                Err(err)
+            }
+        }
+    }
+
+    fn definition(&self, id: u32) -> color_eyre::Result<TokenStream> {
+        let ty = self.lookup(id)?;
+        let (namespace, name) = &self.names[&id];
+        let context = || {
+            format!(
+                "error generating type `{}` ({})",
+                ty.path.segments.join("::"),
+                id
+            )
+        };
+        let root = "super::".repeat(namespace.len() + 1);
+        let ident = ident(name, self.config);
+
+        let definition = match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                let fields = self
+                    .fields(id, &composite.fields, &root, quote!(pub))
+                    .with_context(context)?;
+                let is_struct = composite.fields.iter().all(|field| field.name.is_some());
+                if composite.fields.is_empty() || !is_struct {
+                    quote!(pub struct #ident #fields;)
+                } else {
+                    quote!(pub struct #ident #fields)
+                }
+            }
+            TypeDef::Variant(variant) => {
+                let variants = variant
+                    .variants
+                    .iter()
+                    .map(|variant| self.variant(id, variant, &root))
+                    .collect::<color_eyre::Result<Vec<_>>>()
+                    .with_context(context)?;
+                quote! {
+                    #[allow(non_camel_case_types)]
+                    pub enum #ident { #(#variants)* }
+                }
+            }
+            _ => return Err(eyre::eyre!("Type {} cannot be generated", id)).with_context(context),
+        };
+
+        Ok(quote! {
+            #[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+            #definition
+        })
+    }
+
+    /// Generate the contents of the types module, that is every type
+    /// resolved so far along with all of the types they depend on.
+    pub fn generate(&self) -> TokenStream {
+        let mut module = TypesModule::default();
+        for (id, definition) in self.definitions.borrow().iter() {
+            module.insert(&self.names[id].0, definition.clone());
+        }
+        module.into_token_stream(self.config)
+    }
+}
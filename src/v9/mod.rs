@@ -0,0 +1 @@
+decode_module!(v9);
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{call, event, v13_metadata};
+use frame_metadata::decode_different::DecodeDifferent::Decoded;
+use frame_metadata::v13::*;
+use subxt_gen::{decode_metadata, Config};
+
+fn module(name: &str, index: u8, event_args: &[&str], call_args: &[&str]) -> ModuleMetadata {
+    let names: Vec<String> = (0..call_args.len()).map(|i| format!("arg{}", i)).collect();
+    let arguments: Vec<(&str, &str)> = names
+        .iter()
+        .map(String::as_str)
+        .zip(call_args.iter().copied())
+        .collect();
+    ModuleMetadata {
+        calls: Some(Decoded(vec![call("call", &arguments)])),
+        event: Some(Decoded(vec![event("Happened", event_args)])),
+        ..common::module(name, index)
+    }
+}
+
+/// The generated code without whitespace so it can be searched
+/// regardless of formatting.
+fn generate(modules: Vec<ModuleMetadata>) -> String {
+    decode_metadata(v13_metadata(modules), &Config::default())
+        .unwrap()
+        .to_string()
+        .split_whitespace()
+        .collect()
+}
+
+fn compact(code: &str) -> String {
+    code.split_whitespace().collect()
+}
+
+#[test]
+fn generic_parameters_are_bounded() {
+    let code = generate(vec![module(
+        "Vesting",
+        1,
+        &["VestingInfo<Balance, BlockNumber>"],
+        &[],
+    )]);
+
+    let bounds = "Codec + EncodeLike + Member + Default + Send + Sync";
+    assert!(code.contains(&compact(&format!(
+        "type VestingInfo<A0: {bounds}, A1: {bounds}>: {bounds};",
+        bounds = bounds
+    ))));
+}
+
+#[test]
+fn compact_types_have_a_compact_encoding() {
+    let code = generate(vec![module(
+        "Tokens",
+        1,
+        &["CurrencyId"],
+        &["T::CurrencyId", "Compact<T::Balance>"],
+    )]);
+
+    assert!(code.contains(&compact("#[codec(compact)] pub arg1: T::Balance")));
+    assert!(code.contains(&compact(
+        "type Balance: Codec + EncodeLike + Member + Default + Send + Sync \
+         + parity_scale_codec::HasCompact;"
+    )));
+    assert!(code.contains(&compact(
+        "type CurrencyId: Codec + EncodeLike + Member + Default + Send + Sync;"
+    )));
+}
+
+#[test]
+fn shared_compact_types_have_a_compact_encoding() {
+    let code = generate(vec![
+        module("Tokens", 1, &["Balance"], &[]),
+        module("Vesting", 2, &[], &["Compact<T::Balance>"]),
+    ]);
+
+    assert!(code.contains(&compact(
+        "pub trait SharedTypes: substrate_subxt::system::System { \
+         type Balance: Codec + EncodeLike + Member + Default + Send + Sync \
+         + parity_scale_codec::HasCompact; }"
+    )));
+}
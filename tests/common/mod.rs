@@ -0,0 +1,105 @@
+#![allow(dead_code)]
+
+use frame_metadata::decode_different::DecodeDifferent::Decoded;
+use frame_metadata::v13::*;
+use frame_metadata::{v14, RuntimeMetadata, RuntimeMetadataPrefixed, META_RESERVED};
+use scale_info::form::MetaForm;
+use scale_info::meta_type;
+
+/// V13 metadata made of `modules`.
+pub fn v13_metadata(modules: Vec<ModuleMetadata>) -> RuntimeMetadataPrefixed {
+    RuntimeMetadataPrefixed(
+        META_RESERVED,
+        RuntimeMetadata::V13(RuntimeMetadataV13 {
+            modules: Decoded(modules),
+            extrinsic: ExtrinsicMetadata {
+                version: 4,
+                signed_extensions: vec![],
+            },
+        }),
+    )
+}
+
+/// V14 metadata made of `pallets`.
+pub fn v14_metadata(pallets: Vec<v14::PalletMetadata<MetaForm>>) -> RuntimeMetadataPrefixed {
+    let extrinsic = v14::ExtrinsicMetadata {
+        ty: meta_type::<()>(),
+        version: 4,
+        signed_extensions: vec![],
+    };
+    v14::RuntimeMetadataV14::new(pallets, extrinsic, meta_type::<()>()).into()
+}
+
+/// An empty V13 module, fill it in with struct update syntax.
+pub fn module(name: &str, index: u8) -> ModuleMetadata {
+    ModuleMetadata {
+        name: Decoded(name.to_string()),
+        storage: None,
+        calls: None,
+        event: None,
+        constants: Decoded(vec![]),
+        errors: Decoded(vec![]),
+        index,
+    }
+}
+
+/// An empty V14 pallet, fill it in with struct update syntax.
+pub fn pallet(name: &'static str, index: u8) -> v14::PalletMetadata<MetaForm> {
+    v14::PalletMetadata {
+        name,
+        storage: None,
+        calls: None,
+        event: None,
+        constants: vec![],
+        error: None,
+        index,
+    }
+}
+
+pub fn call(name: &str, arguments: &[(&str, &str)]) -> FunctionMetadata {
+    FunctionMetadata {
+        name: Decoded(name.to_string()),
+        arguments: Decoded(
+            arguments
+                .iter()
+                .map(|(name, ty)| FunctionArgumentMetadata {
+                    name: Decoded(name.to_string()),
+                    ty: Decoded(ty.to_string()),
+                })
+                .collect(),
+        ),
+        documentation: Decoded(vec![]),
+    }
+}
+
+pub fn event(name: &str, arguments: &[&str]) -> EventMetadata {
+    EventMetadata {
+        name: Decoded(name.to_string()),
+        arguments: Decoded(arguments.iter().map(|arg| arg.to_string()).collect()),
+        documentation: Decoded(vec![]),
+    }
+}
+
+pub fn constant(name: &str, ty: &str, value: Vec<u8>) -> ModuleConstantMetadata {
+    ModuleConstantMetadata {
+        name: Decoded(name.to_string()),
+        ty: Decoded(ty.to_string()),
+        value: Decoded(value),
+        documentation: Decoded(vec![]),
+    }
+}
+
+pub fn error(name: &str) -> ErrorMetadata {
+    ErrorMetadata {
+        name: Decoded(name.to_string()),
+        documentation: Decoded(vec![]),
+    }
+}
+
+/// Metadata with a single pallet emitting a single event.
+pub fn metadata() -> RuntimeMetadataPrefixed {
+    v13_metadata(vec![ModuleMetadata {
+        event: Some(Decoded(vec![event("Transferred", &["u32"])])),
+        ..module("Tokens", 1)
+    }])
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{constant, module, v13_metadata};
+use frame_metadata::decode_different::DecodeDifferent::Decoded;
+use frame_metadata::v13::*;
+use frame_metadata::RuntimeMetadataPrefixed;
+use scale::Encode;
+use subxt_gen::{decode_metadata_formatted, Config};
+
+fn metadata(constants: Vec<ModuleConstantMetadata>) -> RuntimeMetadataPrefixed {
+    v13_metadata(vec![ModuleMetadata {
+        constants: Decoded(constants),
+        ..module("Tokens", 1)
+    }])
+}
+
+fn embed_constants() -> Config {
+    Config {
+        embed_constants: true,
+        ..Config::default()
+    }
+}
+
+#[test]
+fn constants_have_an_accessor() {
+    let metadata = metadata(vec![constant("MaxLocks", "u32", 50u32.encode())]);
+
+    let output = decode_metadata_formatted(metadata, &Config::default()).unwrap();
+    assert!(output.contains("pub trait MaxLocksConstantExt<T: Tokens + substrate_subxt::Runtime>"));
+    assert!(output.contains("fn max_locks(&self) -> Result<u32, substrate_subxt::MetadataError>"));
+    assert!(output.contains(
+        "impl<T: Tokens + substrate_subxt::Runtime> MaxLocksConstantExt<T>\n    for substrate_subxt::Client<T>"
+    ));
+    assert!(output.contains(r#"self.metadata().module(MODULE)?.constant("MaxLocks")?.value()"#));
+    assert!(!output.contains("pub const"));
+}
+
+#[test]
+fn primitive_constants_are_embedded() {
+    let metadata = metadata(vec![
+        constant("MaxLocks", "u32", 50u32.encode()),
+        constant("ExistentialDeposit", "T::Balance", 500u128.encode()),
+    ]);
+
+    let output = decode_metadata_formatted(metadata, &embed_constants()).unwrap();
+    assert!(output.contains("pub const MAX_LOCKS: u32 = 50;"));
+    assert!(output.contains(") -> Result<T::Balance, substrate_subxt::MetadataError>;"));
+    assert!(!output.contains("pub const EXISTENTIAL_DEPOSIT"));
+}
+
+#[test]
+fn literals_match_their_type() {
+    let metadata = metadata(vec![
+        constant("Enabled", "bool", true.encode()),
+        constant("Disabled", "bool", false.encode()),
+        constant("U8", "u8", u8::MAX.encode()),
+        constant("U16", "u16", u16::MAX.encode()),
+        constant("U64", "u64", u64::MAX.encode()),
+        constant("U128", "u128", u128::MAX.encode()),
+        constant("I8", "i8", i8::MIN.encode()),
+        constant("I16", "i16", i16::MIN.encode()),
+        constant("I32", "i32", i32::MIN.encode()),
+        constant("I64", "i64", i64::MIN.encode()),
+        constant("I128", "i128", i128::MIN.encode()),
+        // mapped to `u64` by the default types
+        constant("BlockWeight", "Weight", 1_000_000u64.encode()),
+    ]);
+
+    let output = decode_metadata_formatted(metadata, &embed_constants()).unwrap();
+    assert!(output.contains("pub const ENABLED: bool = true;"));
+    assert!(output.contains("pub const DISABLED: bool = false;"));
+    assert!(output.contains(&format!("pub const U8: u8 = {};", u8::MAX)));
+    assert!(output.contains(&format!("pub const U16: u16 = {};", u16::MAX)));
+    assert!(output.contains(&format!("pub const U64: u64 = {};", u64::MAX)));
+    assert!(output.contains(&format!("pub const U128: u128 = {};", u128::MAX)));
+    assert!(output.contains(&format!("pub const I8: i8 = {};", i8::MIN)));
+    assert!(output.contains(&format!("pub const I16: i16 = {};", i16::MIN)));
+    assert!(output.contains(&format!("pub const I32: i32 = {};", i32::MIN)));
+    assert!(output.contains(&format!("pub const I64: i64 = {};", i64::MIN)));
+    assert!(output.contains(&format!("pub const I128: i128 = {};", i128::MIN)));
+    assert!(output.contains("pub const BLOCK_WEIGHT: u64 = 1000000;"));
+}
+
+#[test]
+fn malformed_values_are_reported() {
+    let metadata = metadata(vec![constant("MaxLocks", "u32", vec![50])]);
+
+    let err = decode_metadata_formatted(metadata, &embed_constants()).unwrap_err();
+    let err = format!("{:#}", err);
+    assert!(err.contains("error generating constant `MaxLocks` in pallet `Tokens`"));
+    assert!(err.contains("error decoding the value"));
+}
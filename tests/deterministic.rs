@@ -0,0 +1,81 @@
+mod common;
+
+use common::{call, event, v13_metadata};
+use frame_metadata::decode_different::{DecodeDifferent::Decoded, DecodeDifferentStr};
+use frame_metadata::v13::*;
+use frame_metadata::RuntimeMetadataPrefixed;
+use subxt_gen::{decode_metadata_formatted, Config};
+
+fn s(value: &str) -> DecodeDifferentStr {
+    Decoded(value.to_string())
+}
+
+fn module(name: &str, index: u8, events: Vec<EventMetadata>) -> ModuleMetadata {
+    ModuleMetadata {
+        storage: Some(Decoded(StorageMetadata {
+            prefix: s(name),
+            entries: Decoded(vec![StorageEntryMetadata {
+                name: s("Accounts"),
+                modifier: StorageEntryModifier::Default,
+                ty: StorageEntryType::DoubleMap {
+                    hasher: StorageHasher::Blake2_128Concat,
+                    key1: s("T::AccountId"),
+                    key2_hasher: StorageHasher::Twox64Concat,
+                    key2: s("T::CurrencyId"),
+                    value: s("AccountData<T::Balance>"),
+                },
+                default: Decoded(vec![]),
+                documentation: Decoded(vec![]),
+            }]),
+        })),
+        calls: Some(Decoded(vec![call(
+            "transfer",
+            &[
+                ("dest", "<T::Lookup as StaticLookup>::Source"),
+                ("currency_id", "T::CurrencyId"),
+                ("amount", "Compact<T::Balance>"),
+            ],
+        )])),
+        event: Some(Decoded(events)),
+        ..common::module(name, index)
+    }
+}
+
+fn metadata() -> RuntimeMetadataPrefixed {
+    let modules = vec![
+        module(
+            "Tokens",
+            1,
+            vec![
+                event("Transferred", &["CurrencyId", "AccountId", "Balance"]),
+                event("Locked", &["LockId", "Amount", "Reason", "Duration"]),
+            ],
+        ),
+        module(
+            "Vesting",
+            2,
+            vec![event(
+                "Vested",
+                &[
+                    "Schedule",
+                    "Period",
+                    "Cliff",
+                    "VestingInfo<Balance, BlockNumber>",
+                ],
+            )],
+        ),
+    ];
+
+    v13_metadata(modules)
+}
+
+#[test]
+fn output_is_identical_across_runs() {
+    let config = Config::default();
+    let expected = decode_metadata_formatted(metadata(), &config).unwrap();
+
+    for _ in 0..10 {
+        let output = decode_metadata_formatted(metadata(), &config).unwrap();
+        assert_eq!(expected, output);
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use subxt_gen::Config;
+
+#[test]
+fn system_is_excluded_by_default() {
+    let config = Config::default();
+    assert!(!config.includes_module("System").unwrap());
+    assert!(config.includes_module("Tokens").unwrap());
+}
+
+#[test]
+fn include_overrides_default_exclude() {
+    let config = Config {
+        include: vec!["System".to_string(), "Tok*".to_string()],
+        ..Config::default()
+    };
+    assert!(config.includes_module("System").unwrap());
+    assert!(config.includes_module("Tokens").unwrap());
+    assert!(!config.includes_module("Vesting").unwrap());
+}
+
+#[test]
+fn exclude_overrides_include() {
+    let config = Config {
+        include: vec!["*".to_string()],
+        exclude: vec!["Tokens".to_string()],
+        ..Config::default()
+    };
+    assert!(config.includes_module("System").unwrap());
+    assert!(!config.includes_module("Tokens").unwrap());
+}
+
+mod v13 {
+    use crate::common::{call, module, v13_metadata};
+    use frame_metadata::decode_different::DecodeDifferent::Decoded;
+    use frame_metadata::v13::*;
+    use frame_metadata::RuntimeMetadataPrefixed;
+    use subxt_gen::{decode_metadata_formatted, Config};
+
+    fn metadata(name: &str, calls: Vec<FunctionMetadata>) -> RuntimeMetadataPrefixed {
+        v13_metadata(vec![ModuleMetadata {
+            calls: Some(Decoded(calls)),
+            ..module(name, 1)
+        }])
+    }
+
+    #[test]
+    fn items_match_the_metadata_pallet_name() {
+        let config = Config {
+            items: vec!["EVM::call".to_string()],
+            ..Config::default()
+        };
+        let metadata = metadata(
+            "EVM",
+            vec![
+                call("call", &[("arg", "u32")]),
+                call("create", &[("arg", "u32")]),
+            ],
+        );
+
+        let output = decode_metadata_formatted(metadata, &config).unwrap();
+        assert!(output.contains("pub struct CallCall"));
+        assert!(!output.contains("pub struct CreateCall"));
+    }
+
+    #[test]
+    fn skipped_items_are_not_parsed() {
+        let config = Config {
+            items: vec!["Tokens::transfer".to_string()],
+            ..Config::default()
+        };
+        let metadata = metadata(
+            "Tokens",
+            vec![
+                call("transfer", &[("arg", "u32")]),
+                call("bad", &[("arg", "&bad")]),
+            ],
+        );
+
+        let output = decode_metadata_formatted(metadata, &config).unwrap();
+        assert!(output.contains("pub struct TransferCall"));
+    }
+}
+
+mod v14 {
+    #![allow(dead_code, non_camel_case_types)]
+
+    use crate::common::{pallet, v14_metadata};
+    use frame_metadata::v14::*;
+    use frame_metadata::RuntimeMetadataPrefixed;
+    use scale_info::{meta_type, TypeInfo};
+    use subxt_gen::{decode_metadata_formatted, Config};
+
+    #[derive(TypeInfo)]
+    struct LockInfo {
+        amount: u64,
+    }
+
+    #[derive(TypeInfo)]
+    enum Call {
+        transfer { amount: u32 },
+        lock { info: LockInfo },
+    }
+
+    fn metadata() -> RuntimeMetadataPrefixed {
+        v14_metadata(vec![PalletMetadata {
+            calls: Some(meta_type::<Call>().into()),
+            ..pallet("Tokens", 1)
+        }])
+    }
+
+    #[test]
+    fn skipped_items_do_not_generate_their_types() {
+        let config = Config {
+            items: vec!["Tokens::transfer".to_string()],
+            ..Config::default()
+        };
+
+        let output = decode_metadata_formatted(metadata(), &config).unwrap();
+        assert!(output.contains("pub struct TransferCall"));
+        assert!(!output.contains("LockInfo"));
+
+        let output = decode_metadata_formatted(metadata(), &Config::default()).unwrap();
+        assert!(output.contains("pub struct LockInfo"));
+    }
+}
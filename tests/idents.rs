@@ -0,0 +1,73 @@
+mod common;
+
+use common::{error, event, v13_metadata};
+use frame_metadata::decode_different::DecodeDifferent::Decoded;
+use frame_metadata::v13::*;
+use subxt_gen::{decode_metadata_formatted, Config};
+
+fn module(name: &str, index: u8) -> ModuleMetadata {
+    ModuleMetadata {
+        event: Some(Decoded(vec![event("Happened", &["Balance"])])),
+        errors: Decoded(vec![error("Failed")]),
+        ..common::module(name, index)
+    }
+}
+
+#[test]
+fn pallet_names_are_escaped() {
+    let metadata = v13_metadata(vec![module("Self", 1), module("type", 2)]);
+
+    let output = decode_metadata_formatted(metadata, &Config::default()).unwrap();
+    assert!(output.contains("pub mod self_ {"));
+    assert!(output.contains("pub trait Self_: super::shared_types::SharedTypes"));
+    assert!(output.contains("pub struct HappenedEvent<T: Self_>"));
+    assert!(output.contains("pub mod r#type {"));
+    assert!(output.contains("Self_(super::self_::Error)"));
+    assert!(output.contains("Type(super::r#type::Error)"));
+}
+
+mod v14 {
+    #![allow(dead_code, non_camel_case_types)]
+
+    use crate::common::{pallet, v14_metadata};
+    use frame_metadata::v14::*;
+    use scale_info::build::Fields;
+    use scale_info::{meta_type, Path, Type, TypeInfo};
+    use subxt_gen::{decode_metadata_formatted, Config};
+
+    /// A type whose field names are not Rust identifiers.
+    struct Limits;
+
+    impl TypeInfo for Limits {
+        type Identity = Self;
+
+        fn type_info() -> Type {
+            Type::builder()
+                .path(Path::new("Limits", "pallet_limits::type"))
+                .composite(
+                    Fields::named()
+                        .field(|f| f.ty::<u8>().name("type"))
+                        .field(|f| f.ty::<u32>().name("max-len")),
+                )
+        }
+    }
+
+    #[derive(TypeInfo)]
+    enum Call {
+        set { limits: Limits },
+    }
+
+    #[test]
+    fn registry_names_are_escaped() {
+        let metadata = v14_metadata(vec![PalletMetadata {
+            calls: Some(meta_type::<Call>().into()),
+            ..pallet("Limits", 1)
+        }]);
+
+        let output = decode_metadata_formatted(metadata, &Config::default()).unwrap();
+        assert!(output.contains("pub mod r#type {"));
+        assert!(output.contains("pub r#type: u8,"));
+        assert!(output.contains("pub max_len: u32,"));
+        assert!(output.contains("runtime_types::pallet_limits::r#type::Limits"));
+    }
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use subxt_gen::{decode_metadata_formatted, Config};
+
+fn keep_going() -> Config {
+    Config {
+        keep_going: true,
+        ..Config::default()
+    }
+}
+
+mod v13 {
+    use super::*;
+    use crate::common::{error, module, v13_metadata};
+    use frame_metadata::decode_different::DecodeDifferent::{Decoded, Encode};
+    use frame_metadata::v13::*;
+    use frame_metadata::RuntimeMetadataPrefixed;
+
+    fn metadata() -> RuntimeMetadataPrefixed {
+        let errors = vec![
+            ErrorMetadata {
+                name: Encode("Malformed"),
+                documentation: Decoded(vec![]),
+            },
+            error("Failed"),
+        ];
+        v13_metadata(vec![ModuleMetadata {
+            errors: Decoded(errors),
+            ..module("Tokens", 1)
+        }])
+    }
+
+    #[test]
+    fn malformed_errors_are_skipped() {
+        let err = decode_metadata_formatted(metadata(), &Config::default()).unwrap_err();
+        assert!(format!("{:#}", err).contains("error decoding an error of pallet `Tokens`"));
+
+        let output = decode_metadata_formatted(metadata(), &keep_going()).unwrap();
+        assert!(output.contains("#[codec(index = 1)]"));
+        assert!(output.contains("Failed,"));
+    }
+}
+
+mod v14 {
+    #![allow(dead_code, non_camel_case_types)]
+
+    use super::*;
+    use crate::common::{pallet, v14_metadata};
+    use frame_metadata::v14::*;
+    use frame_metadata::RuntimeMetadataPrefixed;
+    use scale_info::build::Fields;
+    use scale_info::{meta_type, Path, Type, TypeInfo};
+
+    fn metadata() -> RuntimeMetadataPrefixed {
+        v14_metadata(vec![PalletMetadata {
+            // errors should be a variant type
+            error: Some(meta_type::<u32>().into()),
+            ..pallet("Tokens", 1)
+        }])
+    }
+
+    #[test]
+    fn malformed_errors_are_skipped() {
+        let err = decode_metadata_formatted(metadata(), &Config::default()).unwrap_err();
+        assert!(format!("{:#}", err).contains("error decoding the errors of pallet `Tokens`"));
+
+        let output = decode_metadata_formatted(metadata(), &keep_going()).unwrap();
+        assert!(output.contains("pub mod tokens"));
+        assert!(!output.contains("pub enum Error"));
+    }
+
+    /// Compact encoding is only defined for numbers.
+    struct Malformed;
+
+    impl TypeInfo for Malformed {
+        type Identity = Self;
+
+        fn type_info() -> Type {
+            Type::builder()
+                .path(Path::new("Malformed", module_path!()))
+                .composite(
+                    Fields::named()
+                        .field(|f| f.ty::<Lock>().name("lock"))
+                        .field(|f| f.ty::<scale::Compact<Vec<u8>>>().name("data")),
+                )
+        }
+    }
+
+    #[derive(TypeInfo)]
+    struct Lock {
+        amount: u64,
+    }
+
+    #[derive(TypeInfo)]
+    enum Call {
+        transfer { amount: u32 },
+        lock { malformed: Malformed },
+    }
+
+    fn calls_metadata() -> RuntimeMetadataPrefixed {
+        v14_metadata(vec![PalletMetadata {
+            calls: Some(meta_type::<Call>().into()),
+            ..pallet("Tokens", 1)
+        }])
+    }
+
+    #[test]
+    fn malformed_types_skip_the_items_using_them() {
+        let err = decode_metadata_formatted(calls_metadata(), &Config::default()).unwrap_err();
+        let err = format!("{:#}", err);
+        assert!(err.contains("error decoding call `lock` of pallet `Tokens`"));
+        assert!(err.contains("error generating type `keep_going::v14::Malformed`"));
+        assert!(err.contains("error generating field `data`"));
+
+        let output = decode_metadata_formatted(calls_metadata(), &keep_going()).unwrap();
+        assert!(output.contains("pub struct TransferCall"));
+        assert!(!output.contains("pub struct LockCall"));
+        assert!(!output.contains("Malformed"));
+        // pulled in by the malformed type only
+        assert!(!output.contains("pub struct Lock {"));
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{event, module, v13_metadata};
+use frame_metadata::decode_different::DecodeDifferent::Decoded;
+use frame_metadata::v13::*;
+use scale::Encode;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn writes_a_file_per_module() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("out_dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let metadata = v13_metadata(vec![
+        ModuleMetadata {
+            event: Some(Decoded(vec![event("Transferred", &["u32"])])),
+            ..module("Tokens", 1)
+        },
+        ModuleMetadata {
+            event: Some(Decoded(vec![event("Happened", &["u32"])])),
+            ..module("type", 2)
+        },
+    ]);
+    let file = dir.join("metadata.scale");
+    fs::write(&file, metadata.encode()).unwrap();
+
+    let out_dir = dir.join("runtime");
+    let output = Command::new(env!("CARGO_BIN_EXE_subxt-gen"))
+        .arg("--file")
+        .arg(&file)
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let modules = fs::read_to_string(out_dir.join("mod.rs")).unwrap();
+    assert!(modules.contains("pub mod tokens;"));
+    assert!(modules.contains("pub mod r#type;"));
+
+    let tokens = fs::read_to_string(out_dir.join("tokens.rs")).unwrap();
+    assert!(tokens.contains("pub struct TransferredEvent"));
+    let ty = fs::read_to_string(out_dir.join("type.rs")).unwrap();
+    assert!(ty.contains("pub struct HappenedEvent"));
+    assert!(!out_dir.join("r#type.rs").exists());
+}
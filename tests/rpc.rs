@@ -0,0 +1,120 @@
+mod common;
+
+use common::metadata;
+use scale::Encode;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use subxt_gen::fetch_metadata;
+use tungstenite::Message;
+
+/// Serve a single WebSocket connection, `respond` is given the request
+/// and returns the reply, if any, before the connection is closed.
+fn ws_server(respond: fn(serde_json::Value) -> Option<serde_json::Value>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        let request = match socket.read().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            message => panic!("unexpected message {:?}", message),
+        };
+        if let Some(response) = respond(request) {
+            socket.send(Message::Text(response.to_string())).unwrap();
+        }
+        let _ = socket.close(None);
+        let _ = socket.flush();
+    });
+
+    url
+}
+
+/// Serve a single HTTP request with the given JSON body.
+fn http_server(body: serde_json::Value) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim().to_lowercase();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(length) = line.strip_prefix("content-length:") {
+                content_length = length.trim().parse().unwrap();
+            }
+        }
+        let mut request = vec![0; content_length];
+        reader.read_exact(&mut request).unwrap();
+
+        let body = body.to_string();
+        write!(
+            reader.get_mut(),
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+    });
+
+    url
+}
+
+fn metadata_response() -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "result": format!("0x{}", hex::encode(metadata().encode())),
+        "id": 1
+    })
+}
+
+#[test]
+fn fetches_over_websocket() {
+    let url = ws_server(|request| {
+        assert_eq!(request["method"], "state_getMetadata");
+        assert_eq!(request["params"], serde_json::json!(["0x01"]));
+        Some(metadata_response())
+    });
+
+    let decoded = fetch_metadata(&url, Some("0x01".to_string())).unwrap();
+    assert_eq!(decoded.encode(), metadata().encode());
+}
+
+#[test]
+fn fetches_over_http() {
+    let url = http_server(metadata_response());
+
+    let decoded = fetch_metadata(&url, None).unwrap();
+    assert_eq!(decoded.encode(), metadata().encode());
+}
+
+#[test]
+fn reports_rpc_errors() {
+    let url = ws_server(|_| {
+        Some(serde_json::json!({
+            "jsonrpc": "2.0",
+            "error": { "code": -32601, "message": "Method not found" },
+            "id": 1
+        }))
+    });
+
+    let err = format!("{:#}", fetch_metadata(&url, None).unwrap_err());
+    assert!(err.contains("error fetching metadata from the substrate node"));
+    assert!(err.contains("state_getMetadata failed"));
+    assert!(err.contains("Method not found"));
+}
+
+#[test]
+fn reports_close_before_response() {
+    let url = ws_server(|_| None);
+
+    let err = format!("{:#}", fetch_metadata(&url, None).unwrap_err());
+    assert!(err.contains("connection closed before response"), "{}", err);
+}
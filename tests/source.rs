@@ -0,0 +1,70 @@
+mod common;
+
+use common::metadata;
+use scale::Encode;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use subxt_gen::parse_metadata;
+
+fn assert_decodes(input: &[u8]) {
+    let decoded = parse_metadata(input).unwrap();
+    assert_eq!(decoded.encode(), metadata().encode());
+}
+
+#[test]
+fn reads_scale_bytes() {
+    assert_decodes(&metadata().encode());
+}
+
+#[test]
+fn reads_hex() {
+    let hex = hex::encode(metadata().encode());
+    assert_decodes(hex.as_bytes());
+    assert_decodes(format!("0x{}", hex).as_bytes());
+    assert_decodes(format!("0x{}\n", hex).as_bytes());
+}
+
+#[test]
+fn reads_json_rpc_response() {
+    let json = serde_json::json!({
+        "jsonrpc": "2.0",
+        "result": format!("0x{}", hex::encode(metadata().encode())),
+        "id": 1
+    });
+    assert_decodes(json.to_string().as_bytes());
+}
+
+#[test]
+fn rejects_json_without_result() {
+    let json = serde_json::json!({ "jsonrpc": "2.0", "id": 1 });
+    assert!(parse_metadata(json.to_string().as_bytes()).is_err());
+}
+
+#[test]
+fn reads_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_subxt-gen"))
+        .args(["--file", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let hex = format!("0x{}", hex::encode(metadata().encode()));
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(hex.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("pub struct TransferredEvent"));
+}
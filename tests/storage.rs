@@ -0,0 +1,49 @@
+mod common;
+
+use common::{module, v13_metadata};
+use frame_metadata::decode_different::DecodeDifferent::Decoded;
+use frame_metadata::v13::*;
+use frame_metadata::RuntimeMetadataPrefixed;
+use subxt_gen::{decode_metadata_formatted, Config};
+
+fn metadata(hashers: Vec<StorageHasher>) -> RuntimeMetadataPrefixed {
+    v13_metadata(vec![ModuleMetadata {
+        storage: Some(Decoded(StorageMetadata {
+            prefix: Decoded("Tokens".to_string()),
+            entries: Decoded(vec![StorageEntryMetadata {
+                name: Decoded("Locks".to_string()),
+                modifier: StorageEntryModifier::Default,
+                ty: StorageEntryType::NMap {
+                    keys: Decoded(vec!["u32".to_string(), "u64".to_string()]),
+                    hashers: Decoded(hashers),
+                    value: Decoded("u128".to_string()),
+                },
+                default: Decoded(vec![]),
+                documentation: Decoded(vec![]),
+            }]),
+        })),
+        ..module("Tokens", 1)
+    }])
+}
+
+#[test]
+fn n_map_keys_are_hashed_in_order() {
+    let hashers = vec![StorageHasher::Blake2_128Concat, StorageHasher::Twox64Concat];
+    let output = decode_metadata_formatted(metadata(hashers), &Config::default()).unwrap();
+    assert!(output
+        .contains("pub struct LocksStore<T: Tokens>(core::marker::PhantomData<T>, u32, u64);"));
+
+    let first = output.find("blake2_128(&encoded)").unwrap();
+    let second = output.find("twox_64(&encoded)").unwrap();
+    assert!(first < second);
+    assert!(output[..first].contains("let encoded = self.1.encode();"));
+    assert!(output[first..second].contains("let encoded = self.2.encode();"));
+}
+
+#[test]
+fn n_map_keys_match_the_hashers() {
+    let err =
+        decode_metadata_formatted(metadata(vec![StorageHasher::Identity]), &Config::default())
+            .unwrap_err();
+    assert!(format!("{:#}", err).contains("should have a key for each hasher"));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{event, module, v13_metadata};
+use frame_metadata::decode_different::DecodeDifferent::Decoded;
+use frame_metadata::v13::*;
+use std::fs;
+use std::path::PathBuf;
+use subxt_gen::{decode_metadata_formatted, Config};
+
+/// Write `contents` to a file named `name` and read its types into a new config.
+fn read_types(name: &str, contents: &str) -> color_eyre::Result<Config> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("types");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+
+    let mut config = Config::default();
+    config.read_types(&path)?;
+    Ok(config)
+}
+
+#[test]
+fn reads_toml() {
+    let config = read_types("types.toml", "Balance = \"u128\"\n").unwrap();
+    assert_eq!(config.types["Balance"], "u128");
+}
+
+#[test]
+fn reads_json() {
+    let config = read_types("types.json", r#"{ "Balance": "u128" }"#).unwrap();
+    assert_eq!(config.types["Balance"], "u128");
+}
+
+#[test]
+fn dispatches_on_the_extension() {
+    // valid JSON but not valid TOML
+    let err = read_types("json.toml", r#"{ "Balance": "u128" }"#).unwrap_err();
+    assert!(format!("{:#}", err).contains("error reading types from"));
+    assert!(format!("{:#}", err).contains("json.toml"));
+
+    let err = read_types("toml.json", "Balance = \"u128\"\n").unwrap_err();
+    assert!(format!("{:#}", err).contains("error reading types from"));
+    assert!(format!("{:#}", err).contains("toml.json"));
+}
+
+#[test]
+fn rejects_other_extensions() {
+    let err = read_types("types.yaml", "Balance: u128\n").unwrap_err();
+    assert!(err
+        .to_string()
+        .ends_with("types.yaml should be a .toml or .json file"));
+}
+
+#[test]
+fn mapped_types_are_not_associated_types() {
+    let metadata = || {
+        v13_metadata(vec![ModuleMetadata {
+            event: Some(Decoded(vec![event("Transferred", &["Balance"])])),
+            ..module("Tokens", 1)
+        }])
+    };
+
+    let output = decode_metadata_formatted(metadata(), &Config::default()).unwrap();
+    assert!(output.contains("type Balance:"));
+    assert!(output.contains("pub T::Balance,"));
+
+    let config = read_types("balance.toml", "Balance = \"u128\"\n").unwrap();
+    let output = decode_metadata_formatted(metadata(), &config).unwrap();
+    assert!(!output.contains("type Balance:"));
+    assert!(output.contains("pub u128)"));
+}
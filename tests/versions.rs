@@ -0,0 +1,45 @@
+use frame_metadata::decode_different::DecodeDifferent::Decoded;
+use frame_metadata::v12::*;
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed, META_RESERVED};
+use subxt_gen::{decode_metadata_formatted, Config};
+
+fn v12_metadata() -> RuntimeMetadataPrefixed {
+    RuntimeMetadataPrefixed(
+        META_RESERVED,
+        RuntimeMetadata::V12(RuntimeMetadataV12 {
+            modules: Decoded(vec![ModuleMetadata {
+                name: Decoded("Tokens".to_string()),
+                storage: None,
+                calls: Some(Decoded(vec![FunctionMetadata {
+                    name: Decoded("transfer".to_string()),
+                    arguments: Decoded(vec![FunctionArgumentMetadata {
+                        name: Decoded("amount".to_string()),
+                        ty: Decoded("Compact<T::Balance>".to_string()),
+                    }]),
+                    documentation: Decoded(vec![]),
+                }])),
+                event: Some(Decoded(vec![EventMetadata {
+                    name: Decoded("Transferred".to_string()),
+                    arguments: Decoded(vec!["Balance".to_string()]),
+                    documentation: Decoded(vec![]),
+                }])),
+                constants: Decoded(vec![]),
+                errors: Decoded(vec![]),
+                index: 3,
+            }]),
+            extrinsic: ExtrinsicMetadata {
+                version: 4,
+                signed_extensions: vec![],
+            },
+        }),
+    )
+}
+
+#[test]
+fn v12_metadata_is_supported() {
+    let output = decode_metadata_formatted(v12_metadata(), &Config::default()).unwrap();
+    assert!(output.contains("pub mod tokens {"));
+    assert!(output.contains("pub struct TransferCall<T: Tokens + substrate_subxt::Runtime>"));
+    assert!(output.contains("#[codec(compact)]\n        pub amount: T::Balance,"));
+    assert!(output.contains("pub struct TransferredEvent<T: Tokens>"));
+}